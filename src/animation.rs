@@ -0,0 +1,155 @@
+//! Keyframe animations defined in `[animations.<name>]` config tables
+//!
+//! Each property (alpha, offsets, scale, rotation) is interpolated between
+//! the keyframes that set it, using the easing of the keyframe a segment
+//! starts at.
+
+/// Timing function for one keyframe segment
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Overshoots and oscillates into place
+    Elastic,
+    /// Damped spring: quick overshoot, settles smoothly
+    Spring,
+    /// Jumps in `n` equal steps
+    Steps(u32),
+}
+
+impl Easing {
+    /// Parse an easing name like `ease-out-cubic` or `steps(4)`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase().replace('_', "-");
+        if let Some(n) = s.strip_prefix("steps(").and_then(|r| r.strip_suffix(')')) {
+            return n.trim().parse().ok().filter(|n| *n > 0).map(Easing::Steps);
+        }
+        match s.as_str() {
+            "linear" => Some(Easing::Linear),
+            "ease-in" | "ease-in-cubic" => Some(Easing::EaseIn),
+            "ease-out" | "ease-out-cubic" => Some(Easing::EaseOut),
+            "ease-in-out" | "ease-in-out-cubic" => Some(Easing::EaseInOut),
+            "elastic" | "ease-out-elastic" => Some(Easing::Elastic),
+            "spring" => Some(Easing::Spring),
+            "steps" => Some(Easing::Steps(1)),
+            _ => None,
+        }
+    }
+
+    /// Map linear progress (0.0-1.0) to eased progress
+    pub fn apply(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        if x >= 1.0 {
+            return 1.0;
+        }
+        match self {
+            Easing::Linear => x,
+            Easing::EaseIn => x.powi(3),
+            Easing::EaseOut => 1.0 - (1.0 - x).powi(3),
+            Easing::EaseInOut => {
+                if x < 0.5 {
+                    4.0 * x.powi(3)
+                } else {
+                    1.0 - (-2.0 * x + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Elastic => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    let c4 = 2.0 * std::f64::consts::PI / 3.0;
+                    2f64.powf(-10.0 * x) * ((x * 10.0 - 0.75) * c4).sin() + 1.0
+                }
+            }
+            Easing::Spring => 1.0 - (-6.0 * x).exp() * (12.0 * x).cos(),
+            Easing::Steps(n) => (x * *n as f64).floor() / *n as f64,
+        }
+    }
+}
+
+/// One keyframe; properties left unset are interpolated from other keyframes
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Keyframe {
+    /// Seconds from the start of the animation
+    pub time: f64,
+    pub alpha: Option<f64>,
+    pub offset_x: Option<f64>,
+    pub offset_y: Option<f64>,
+    pub scale: Option<f64>,
+    /// Degrees, clockwise
+    pub rotation: Option<f64>,
+    /// Easing of the segment from this keyframe to the next
+    pub easing: Easing,
+}
+
+/// Animated values at one point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub alpha: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub scale: f64,
+    pub rotation: f64,
+}
+
+impl Default for Sample {
+    fn default() -> Self {
+        Self { alpha: 1.0, offset_x: 0.0, offset_y: 0.0, scale: 1.0, rotation: 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyframeAnimation {
+    pub name: String,
+    /// Sorted by time
+    pub keyframes: Vec<Keyframe>,
+    /// Start over after the last keyframe instead of holding it
+    pub repeat: bool,
+}
+
+impl KeyframeAnimation {
+    pub fn new(name: &str, mut keyframes: Vec<Keyframe>, repeat: bool) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { name: name.to_string(), keyframes, repeat }
+    }
+
+    /// Length of one cycle in seconds
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().map(|k| k.time).unwrap_or(0.0)
+    }
+
+    /// Animated values `t` seconds after the animation started
+    pub fn sample(&self, t: f64) -> Sample {
+        let duration = self.duration();
+        let t = if self.repeat && duration > 0.0 { t.rem_euclid(duration) } else { t };
+        let default = Sample::default();
+        Sample {
+            alpha: self.track(t, |k| k.alpha).unwrap_or(default.alpha),
+            offset_x: self.track(t, |k| k.offset_x).unwrap_or(default.offset_x),
+            offset_y: self.track(t, |k| k.offset_y).unwrap_or(default.offset_y),
+            scale: self.track(t, |k| k.scale).unwrap_or(default.scale),
+            rotation: self.track(t, |k| k.rotation).unwrap_or(default.rotation),
+        }
+    }
+
+    /// Interpolate one property over the keyframes that set it
+    fn track(&self, t: f64, value: impl Fn(&Keyframe) -> Option<f64>) -> Option<f64> {
+        let mut points = self.keyframes.iter().filter_map(|k| Some((k.time, value(k)?, k.easing)));
+        let mut previous = points.next()?;
+        if t <= previous.0 {
+            return Some(previous.1);
+        }
+        for next in points {
+            let ((t0, v0, easing), (t1, v1, _)) = (previous, next);
+            if t < t1 {
+                let progress = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+                return Some(v0 + (v1 - v0) * easing.apply(progress));
+            }
+            previous = next;
+        }
+        Some(previous.1)
+    }
+}
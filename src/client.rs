@@ -0,0 +1,276 @@
+//! Command line client for a running daemon
+//!
+//! `inno show|hide|reload|state|dnd|history` call org.inno.Control on the
+//! session bus, or use the control socket when the daemon runs `--no-dbus`.
+
+use crate::ipc;
+use serde_json::json;
+use std::collections::HashMap;
+use zbus::zvariant::Value;
+
+/// Exit code for bad arguments
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code when no daemon is running, as for LSB `status`
+pub const EXIT_NOT_RUNNING: i32 = 3;
+
+/// Subcommands handled by `run`
+pub const COMMANDS: &[&str] = &["show", "hide", "reload", "state", "dnd", "history"];
+
+/// A device as (path, event, percent, state, name)
+type DeviceInfo = (String, String, f64, String, String);
+
+#[zbus::proxy(
+    interface = "org.inno.Control",
+    default_service = "org.inno.Control",
+    default_path = "/org/inno/Control"
+)]
+pub trait Control {
+    fn show_ex(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<u32>;
+    fn hide(&self) -> zbus::Result<()>;
+    fn reload(&self) -> zbus::Result<()>;
+    fn get_state(&self) -> zbus::Result<(f64, String)>;
+    fn get_devices(&self) -> zbus::Result<Vec<DeviceInfo>>;
+    fn get_history(&self) -> zbus::Result<Vec<(u32, String, String, i64)>>;
+    #[zbus(property)]
+    fn do_not_disturb(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_do_not_disturb(&self, enabled: bool) -> zbus::Result<()>;
+}
+
+/// Connect to the daemon, or `None` if it is not running
+pub async fn connect() -> Option<ControlProxy<'static>> {
+    let conn = zbus::Connection::session().await.ok()?;
+    let dbus = zbus::fdo::DBusProxy::new(&conn).await.ok()?;
+    let name = crate::control::BUS_NAME.try_into().ok()?;
+    if !dbus.name_has_owner(name).await.ok()? {
+        return None;
+    }
+    ControlProxy::new(&conn).await.ok()
+}
+
+/// A parsed subcommand
+enum Command {
+    Show(HashMap<&'static str, Value<'static>>),
+    Hide,
+    Reload,
+    State,
+    /// `None` prints the current setting
+    Dnd(Option<DndAction>),
+    History,
+}
+
+enum DndAction {
+    On,
+    Off,
+    Toggle,
+}
+
+fn parse(command: &str, args: &[String]) -> anyhow::Result<Command> {
+    Ok(match command {
+        "show" => Command::Show(parse_show(args)?),
+        "hide" => Command::Hide,
+        "reload" => Command::Reload,
+        "state" => Command::State,
+        "dnd" => Command::Dnd(match args.first().map(String::as_str) {
+            None => None,
+            Some("on") => Some(DndAction::On),
+            Some("off") => Some(DndAction::Off),
+            Some("toggle") => Some(DndAction::Toggle),
+            Some(other) => anyhow::bail!("expected on, off or toggle, got '{}'", other),
+        }),
+        "history" => Command::History,
+        _ => anyhow::bail!("unknown command"),
+    })
+}
+
+/// Run subcommand `command` with `args` and return the exit code
+pub async fn run(command: &str, args: &[String]) -> i32 {
+    let parsed = match parse(command, args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("inno {}: {}", command, e);
+            return EXIT_USAGE;
+        }
+    };
+    let result = match connect().await {
+        Some(proxy) => match parsed {
+            Command::Show(options) => show(&proxy, options).await,
+            Command::Hide => proxy.hide().await.map_err(Into::into),
+            Command::Reload => proxy.reload().await.map_err(Into::into),
+            Command::State => state(&proxy).await,
+            Command::Dnd(action) => dnd(&proxy, action).await,
+            Command::History => history(&proxy).await,
+        },
+        None if ipc::is_running() => run_socket(parsed).await,
+        None => {
+            eprintln!("inno: no daemon is running");
+            return EXIT_NOT_RUNNING;
+        }
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("inno {}: {}", command, e);
+            1
+        }
+    }
+}
+
+/// `show` options as `ShowEx` takes them; the first free argument is the message
+fn parse_show(args: &[String]) -> anyhow::Result<HashMap<&'static str, Value<'static>>> {
+    let mut options = HashMap::new();
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if !flag.starts_with("--") {
+            if options.contains_key("message") {
+                anyhow::bail!("unexpected argument '{}'", flag);
+            }
+            options.insert("message", Value::from(flag.to_string()));
+            i += 1;
+            continue;
+        }
+        let Some(value) = args.get(i + 1) else {
+            anyhow::bail!("missing value for {}", flag);
+        };
+        let (key, value) = match flag {
+            "--duration" => ("duration", Value::from(value.parse::<u32>()?)),
+            "--percent" => ("percent", Value::from(value.parse::<f64>()?)),
+            "--body" => ("body", Value::from(value.clone())),
+            "--icon" => ("icon", Value::from(value.clone())),
+            "--color" => ("color", Value::from(value.clone())),
+            "--animation" => ("animation", Value::from(value.clone())),
+            "--sound" => ("sound", Value::from(value.clone())),
+            "--priority" => ("priority", Value::from(value.clone())),
+            "--position" => ("position", Value::from(value.clone())),
+            "--signal" => ("signal", Value::from(value.clone())),
+            _ => anyhow::bail!("unknown option {}", flag),
+        };
+        options.insert(key, value);
+        i += 2;
+    }
+    if !options.contains_key("message") && !options.contains_key("signal") {
+        anyhow::bail!("a message or --signal is required");
+    }
+    Ok(options)
+}
+
+async fn show(proxy: &ControlProxy<'_>, options: HashMap<&str, Value<'_>>) -> anyhow::Result<()> {
+    let id = proxy.show_ex(options).await?;
+    println!("{}", id);
+    Ok(())
+}
+
+async fn state(proxy: &ControlProxy<'_>) -> anyhow::Result<()> {
+    let (percent, state) = proxy.get_state().await?;
+    println!("battery: {:.0}% {}", percent, state);
+    for (path, event, percent, state, name) in proxy.get_devices().await? {
+        println!("{}: {:.0}% {} ({}, {})", name, percent, state, event, path);
+    }
+    Ok(())
+}
+
+async fn dnd(proxy: &ControlProxy<'_>, action: Option<DndAction>) -> anyhow::Result<()> {
+    let enabled = match action {
+        Some(DndAction::On) => true,
+        Some(DndAction::Off) => false,
+        Some(DndAction::Toggle) => !proxy.do_not_disturb().await?,
+        None => {
+            println!("{}", if proxy.do_not_disturb().await? { "on" } else { "off" });
+            return Ok(());
+        }
+    };
+    proxy.set_do_not_disturb(enabled).await?;
+    println!("{}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+async fn history(proxy: &ControlProxy<'_>) -> anyhow::Result<()> {
+    let now = crate::control::unix_time();
+    for (id, event, text, time) in proxy.get_history().await? {
+        let ago = format!("{} ago", duration(now - time));
+        println!("{:>4}  {:>8}  {}: {}", id, ago, event, text);
+    }
+    Ok(())
+}
+
+/// `run` over the control socket, for a daemon without DBus
+async fn run_socket(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Show(options) => {
+            let mut request = json!({ "cmd": "show" });
+            for (key, value) in &options {
+                request[*key] = match value {
+                    Value::U32(n) => json!(n),
+                    Value::F64(n) => json!(n),
+                    Value::Str(s) => json!(s.as_str()),
+                    other => anyhow::bail!("unexpected value for {}: {:?}", key, other),
+                };
+            }
+            println!("{}", ipc::request(&request).await?["id"]);
+        }
+        Command::Hide => {
+            ipc::request(&json!({ "cmd": "hide" })).await?;
+        }
+        Command::Reload => {
+            ipc::request(&json!({ "cmd": "reload" })).await?;
+        }
+        Command::State => {
+            let state = ipc::request(&json!({ "cmd": "state" })).await?;
+            let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+            let percent = state["percent"].as_f64().unwrap_or_default();
+            println!("battery: {:.0}% {}", percent, text(&state["state"]));
+            for device in state["devices"].as_array().into_iter().flatten() {
+                println!(
+                    "{}: {:.0}% {} ({}, {})",
+                    text(&device["name"]),
+                    device["percent"].as_f64().unwrap_or_default(),
+                    text(&device["state"]),
+                    text(&device["event"]),
+                    text(&device["path"])
+                );
+            }
+        }
+        Command::Dnd(action) => {
+            let mut request = json!({ "cmd": "dnd" });
+            match action {
+                Some(DndAction::On) => request["enabled"] = json!(true),
+                Some(DndAction::Off) => request["enabled"] = json!(false),
+                Some(DndAction::Toggle) => {
+                    let enabled = ipc::request(&request).await?["enabled"] == true;
+                    request["enabled"] = json!(!enabled);
+                }
+                None => {}
+            }
+            let enabled = ipc::request(&request).await?["enabled"] == true;
+            println!("{}", if enabled { "on" } else { "off" });
+        }
+        Command::History => {
+            let now = crate::control::unix_time();
+            let history = ipc::request(&json!({ "cmd": "history" })).await?;
+            for entry in history["history"].as_array().into_iter().flatten() {
+                let ago = format!("{} ago", duration(now - entry["time"].as_i64().unwrap_or(now)));
+                let (event, text) = (&entry["event"], &entry["text"]);
+                println!(
+                    "{:>4}  {:>8}  {}: {}",
+                    entry["id"].as_u64().unwrap_or_default(),
+                    ago,
+                    event.as_str().unwrap_or_default(),
+                    text.as_str().unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A duration in seconds as `12s`, `5m`, `3h` or `2d`
+pub fn duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
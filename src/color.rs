@@ -0,0 +1,300 @@
+//! Colour strings from the config file
+//!
+//! Accepts `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()`,
+//! `hsl()`/`hsla()` and the CSS named colours, returning RGBA in 0.0-1.0.
+
+use crate::config::ColorStop;
+
+/// Parse a colour string, or `None` if it is not a colour
+pub fn parse_color(s: &str) -> Option<(f64, f64, f64, f64)> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    let lower = s.to_ascii_lowercase();
+    if let Some((name, args)) = lower.strip_suffix(')').and_then(|f| f.split_once('(')) {
+        let args: Vec<&str> = args
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|a| !a.is_empty())
+            .collect();
+        return match name.trim() {
+            "rgb" | "rgba" => parse_rgb_args(&args),
+            "hsl" | "hsla" => parse_hsl_args(&args),
+            _ => None,
+        };
+    }
+
+    named_color(&lower)
+}
+
+/// Interpolate a colour ramp of `(position, colour)` stops sorted by position,
+/// holding the end colours outside the ramp
+pub fn ramp_color(ramp: &[ColorStop], value: f64) -> Option<(f64, f64, f64, f64)> {
+    let first = ramp.first()?;
+    if value <= first.0 {
+        return Some(first.1);
+    }
+    for pair in ramp.windows(2) {
+        let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+        if value < p1 {
+            let t = if p1 > p0 { (value - p0) / (p1 - p0) } else { 1.0 };
+            return Some((
+                c0.0 + (c1.0 - c0.0) * t,
+                c0.1 + (c1.1 - c0.1) * t,
+                c0.2 + (c1.2 - c0.2) * t,
+                c0.3 + (c1.3 - c0.3) * t,
+            ));
+        }
+    }
+    ramp.last().map(|stop| stop.1)
+}
+
+fn parse_hex(hex: &str) -> Option<(f64, f64, f64, f64)> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        // Short forms repeat each digit: #f80 == #ff8800
+        3 | 4 => hex
+            .chars()
+            .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16).ok())
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let channel = |i: usize| digits.get(i).map(|&v| v as f64 / 255.0);
+    Some((channel(0)?, channel(1)?, channel(2)?, channel(3).unwrap_or(1.0)))
+}
+
+/// A colour channel: `0-255` or a percentage
+fn parse_channel(s: &str) -> Option<f64> {
+    let v = match s.strip_suffix('%') {
+        Some(pct) => pct.parse::<f64>().ok()? / 100.0,
+        None => s.parse::<f64>().ok()? / 255.0,
+    };
+    Some(v.clamp(0.0, 1.0))
+}
+
+/// An alpha value: `0.0-1.0` or a percentage
+fn parse_alpha(s: Option<&&str>) -> Option<f64> {
+    let Some(s) = s else {
+        return Some(1.0);
+    };
+    let v = match s.strip_suffix('%') {
+        Some(pct) => pct.parse::<f64>().ok()? / 100.0,
+        None => s.parse::<f64>().ok()?,
+    };
+    Some(v.clamp(0.0, 1.0))
+}
+
+fn parse_rgb_args(args: &[&str]) -> Option<(f64, f64, f64, f64)> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    Some((
+        parse_channel(args[0])?,
+        parse_channel(args[1])?,
+        parse_channel(args[2])?,
+        parse_alpha(args.get(3))?,
+    ))
+}
+
+fn parse_hsl_args(args: &[&str]) -> Option<(f64, f64, f64, f64)> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let hue = args[0].trim_end_matches("deg").parse::<f64>().ok()?.rem_euclid(360.0) / 360.0;
+    let percent = |s: &str| -> Option<f64> {
+        Some((s.strip_suffix('%')?.parse::<f64>().ok()? / 100.0).clamp(0.0, 1.0))
+    };
+    let (sat, light) = (percent(args[1])?, percent(args[2])?);
+    let (r, g, b) = hsl_to_rgb(hue, sat, light);
+    Some((r, g, b, parse_alpha(args.get(3))?))
+}
+
+/// Convert HSL (all 0.0-1.0) to RGB
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    if s == 0.0 {
+        return (l, l, l);
+    }
+    let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+    let p = 2.0 * l - q;
+    let hue = |t: f64| {
+        let t = t.rem_euclid(1.0);
+        if t < 1.0 / 6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2.0 / 3.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        }
+    };
+    (hue(h + 1.0 / 3.0), hue(h), hue(h - 1.0 / 3.0))
+}
+
+/// Look up a CSS named colour
+fn named_color(name: &str) -> Option<(f64, f64, f64, f64)> {
+    if name == "transparent" {
+        return Some((0.0, 0.0, 0.0, 0.0));
+    }
+    let rgb = CSS_COLORS.iter().find(|(n, _)| *n == name).map(|(_, rgb)| *rgb)?;
+    let channel = |shift: u32| ((rgb >> shift) & 0xff) as f64 / 255.0;
+    Some((channel(16), channel(8), channel(0), 1.0))
+}
+
+const CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
@@ -1,13 +1,18 @@
+use crate::animation::{Easing, Keyframe, KeyframeAnimation};
+use crate::color;
 use cairo::{FontSlant, FontWeight};
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
 
 // Constants
 pub const DEFAULT_MARGIN: i32 = 10;
 pub const DEFAULT_FONT_SIZE: f64 = 24.0;
 pub const DEFAULT_ICON_SIZE: f64 = 24.0;
+/// Seconds per frame of an animated icon
+pub const DEFAULT_ICON_INTERVAL: f64 = 0.5;
 pub const HIDE_TIMEOUT_SECS: u64 = 86400;
 
 #[derive(Debug, Error)]
@@ -19,6 +24,10 @@ pub enum ConfigError {
     ReadError(#[from] std::io::Error),
     #[error("Parse error in config: {0}")]
     ParseError(#[from] toml::de::Error),
+    #[error("Invalid colour '{value}' for {field}")]
+    InvalidColor { field: String, value: String },
+    #[error("Invalid animation '{name}': {reason}")]
+    InvalidAnimation { name: String, reason: String },
 }
 
 // TOML config file structure
@@ -26,8 +35,13 @@ pub enum ConfigError {
 struct ConfigFile {
     general: Option<GeneralConfig>,
     appearance: Option<AppearanceConfig>,
+    bar: Option<BarConfig>,
+    layout: Option<LayoutConfig>,
+    pointer: Option<PointerConfig>,
     #[serde(default)]
-    colors: HashMap<String, [f64; 4]>,
+    colors: HashMap<String, ColorValue>,
+    #[serde(default)]
+    animations: HashMap<String, AnimationConfig>,
     #[serde(default)]
     signal: Vec<SignalConfig>,
 }
@@ -43,41 +57,214 @@ struct GeneralConfig {
     output: Option<String>,
     battery_mode: Option<String>,
     fps: Option<u64>,
+    max_width: Option<f64>,
+    max_lines: Option<u32>,
+    ellipsize: Option<String>,
+    markup: Option<bool>,
+    icon_theme: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 struct AppearanceConfig {
-    text_color: Option<[f64; 4]>,
-    bg_color: Option<[f64; 4]>,
-    border_radius: Option<f64>,
-    gradient: Option<bool>,
+    text_color: Option<ColorValue>,
+    bg_color: Option<ColorValue>,
+    border_radius: Option<RadiusConfig>,
+    border_width: Option<f64>,
+    border_color: Option<ColorValue>,
+    gradient: Option<GradientConfig>,
+    shadow: Option<ShadowConfig>,
+    color_ramp: Option<Vec<(f64, ColorValue)>>,
+}
+
+/// `border_radius = 8` or per corner `[top_left, top_right, bottom_right, bottom_left]`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RadiusConfig {
+    All(f64),
+    Corners([f64; 4]),
+}
+
+/// `gradient = true` for the classic darkening, or a table with stops
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum GradientConfig {
+    Enabled(bool),
+    Stops(GradientStopsConfig),
+}
+
+#[derive(Debug, Deserialize)]
+struct GradientStopsConfig {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    angle: Option<f64>,
+    stops: Vec<(f64, ColorValue)>,
+}
+
+/// `shadow = true` for the default shadow, or a table
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ShadowConfig {
+    Enabled(bool),
+    Spec(ShadowSpecConfig),
+}
+
+#[derive(Debug, Deserialize)]
+struct ShadowSpecConfig {
+    color: Option<ColorValue>,
+    blur: Option<f64>,
+    offset: Option<[f64; 2]>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct BarConfig {
+    width: Option<f64>,
+    height: Option<f64>,
+    color: Option<ColorValue>,
+    track_color: Option<ColorValue>,
+    rounded: Option<bool>,
+    low_color: Option<ColorValue>,
+    high_color: Option<ColorValue>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PointerConfig {
+    left_click: Option<String>,
+    middle_click: Option<String>,
+    right_click: Option<String>,
+    hover_pause: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct LayoutConfig {
+    direction: Option<String>,
+    items: Option<Vec<LayoutItemConfig>>,
+    padding: Option<PaddingConfig>,
+    spacing: Option<f64>,
+    align: Option<String>,
+    justify: Option<String>,
+    min_width: Option<f64>,
+    max_width: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+    // Only read from the top-level [layout] section
+    animation_margin: Option<f64>,
+    timestamp_format: Option<String>,
+}
+
+/// A layout item is either an element name or a nested box
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LayoutItemConfig {
+    Element(String),
+    Box(Box<LayoutConfig>),
+}
+
+/// `padding = 10`, `padding = [vertical, horizontal]` or `[top, right, bottom, left]`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PaddingConfig {
+    All(f64),
+    Sides(Vec<f64>),
+}
+
+#[derive(Debug, Deserialize)]
+struct AnimationConfig {
+    #[serde(default)]
+    repeat: bool,
+    keyframes: Vec<KeyframeConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyframeConfig {
+    time: f64,
+    alpha: Option<f64>,
+    offset_x: Option<f64>,
+    offset_y: Option<f64>,
+    scale: Option<f64>,
+    rotation: Option<f64>,
+    easing: Option<String>,
+}
+
+/// A colour: `[r, g, b, a]` (0.0-1.0), a `[colors]` name, or a CSS colour string
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+enum ColorValue {
+    Rgba([f64; 4]),
+    Text(String),
+}
+
+/// One icon, or a list of frames shown in turn
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum IconConfig {
+    Single(String),
+    Frames(Vec<String>),
+}
+
+impl Default for IconConfig {
+    fn default() -> Self {
+        IconConfig::Single(String::new())
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct SignalConfig {
     message: String,
     #[serde(default)]
-    icon: String,
+    icon: IconConfig,
+    /// Seconds per frame of an icon list
+    icon_interval: Option<f64>,
     icon_size: Option<f64>,
-    color: String,
+    icon_recolor: Option<bool>,
+    color: Option<ColorValue>,
+    /// `[[percent, colour], ...]` replacing `color` when the percentage is known
+    color_ramp: Option<Vec<(f64, ColorValue)>>,
     threshold: f64,
     state: String,
     #[serde(default)]
     animation: String,
+    animation_in: Option<String>,
+    animation_idle: Option<String>,
+    animation_out: Option<String>,
     duration: Option<u64>,
     sound: Option<String>,
+    #[serde(default)]
+    bar: bool,
+    body: Option<String>,
+    layout: Option<LayoutConfig>,
+    gradient: Option<GradientConfig>,
+    border_width: Option<f64>,
+    border_color: Option<ColorValue>,
+    shadow: Option<ShadowConfig>,
+    bg_color: Option<ColorValue>,
+    font: Option<String>,
+    font_size: Option<f64>,
+    border_radius: Option<RadiusConfig>,
+    #[serde(alias = "anchor")]
+    position: Option<String>,
 }
 
 // Runtime config structures
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum Animation {
+    #[default]
     None,
     Blink,
     Pulse,
     Fade,
     SlideLeft,
     SlideRight,
+    /// Slide in from the bottom and top edges
+    SlideUp,
+    SlideDown,
+    /// Grow from the centre
+    Zoom,
+    Shake,
+    /// Reveal the text one character at a time
+    Typewriter,
     Bounce,
+    /// A keyframe animation from an `[animations.<name>]` table
+    Custom(Arc<KeyframeAnimation>),
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -97,7 +284,16 @@ pub enum BatteryMode {
     Lowest,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Ellipsize {
+    None,
+    Start,
+    Middle,
+    #[default]
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum HAnchor {
     Left,
     #[default]
@@ -105,7 +301,7 @@ pub enum HAnchor {
     Right,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum VAnchor {
     Top,
     Center,
@@ -113,7 +309,7 @@ pub enum VAnchor {
     Bottom,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Anchor {
     pub h: HAnchor,
     pub v: VAnchor,
@@ -140,17 +336,354 @@ impl Anchor {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Direction {
+    #[default]
+    Row,
+    Column,
+}
+
+impl Direction {
+    fn flipped(self) -> Self {
+        match self {
+            Direction::Row => Direction::Column,
+            Direction::Column => Direction::Row,
+        }
+    }
+}
+
+/// Placement of children inside a box, along either axis
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Align {
+    Start,
+    #[default]
+    Center,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Padding {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Padding {
+    pub fn all(v: f64) -> Self {
+        Self { top: v, right: v, bottom: v, left: v }
+    }
+}
+
+/// Content pieces a layout can place
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Element {
+    Icon,
+    /// The formatted notification text
+    Title,
+    /// The signal's secondary `body` text
+    Body,
+    Bar,
+    Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutItem {
+    Element(Element),
+    Box(LayoutBox),
+}
+
+/// A row or column of elements and nested boxes. Elements with nothing to
+/// show (no icon, no percentage for the bar, ...) are skipped entirely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutBox {
+    pub direction: Direction,
+    pub items: Vec<LayoutItem>,
+    pub padding: Padding,
+    /// Gap between neighbouring items
+    pub spacing: f64,
+    /// Cross-axis placement of each item
+    pub align: Align,
+    /// Main-axis placement when the box is larger than its content
+    pub justify: Align,
+    pub min_width: Option<f64>,
+    pub max_width: Option<f64>,
+    /// Fixed size, overriding the measured content size
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+impl Default for LayoutBox {
+    /// Icon, text and bar in a single row: the classic inno look
+    fn default() -> Self {
+        Self {
+            direction: Direction::Row,
+            items: vec![
+                LayoutItem::Element(Element::Icon),
+                LayoutItem::Element(Element::Title),
+                LayoutItem::Element(Element::Bar),
+            ],
+            padding: Padding::all(10.0),
+            spacing: 10.0,
+            align: Align::Center,
+            justify: Align::Start,
+            min_width: None,
+            max_width: None,
+            width: None,
+            height: None,
+        }
+    }
+}
+
+/// Corner radii of the notification background
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CornerRadius {
+    pub top_left: f64,
+    pub top_right: f64,
+    pub bottom_right: f64,
+    pub bottom_left: f64,
+}
+
+impl CornerRadius {
+    pub fn all(r: f64) -> Self {
+        Self { top_left: r, top_right: r, bottom_right: r, bottom_left: r }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.top_left <= 0.0
+            && self.top_right <= 0.0
+            && self.bottom_right <= 0.0
+            && self.bottom_left <= 0.0
+    }
+}
+
+/// Gradient stop: offset (0.0-1.0) and RGBA colour
+pub type ColorStop = (f64, (f64, f64, f64, f64));
+
+/// Background gradient
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gradient {
+    /// `gradient = true`: `bg_color` darkening from left to right
+    Darken,
+    /// Stops along a line at `angle` degrees (0 = left to right, 90 = top to bottom)
+    Linear { angle: f64, stops: Vec<ColorStop> },
+    /// Stops from the centre out to the corners
+    Radial { stops: Vec<ColorStop> },
+}
+
+/// Soft drop shadow drawn in the transparent area around the notification
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    pub color: (f64, f64, f64, f64),
+    /// Blur radius in pixels
+    pub blur: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self { color: (0.0, 0.0, 0.0, 0.5), blur: 8.0, offset_x: 0.0, offset_y: 2.0 }
+    }
+}
+
+/// What a signal shows as its icon
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Icon {
+    #[default]
+    None,
+    /// A text glyph drawn with the main font (e.g. a Nerd Font icon)
+    Glyph(String),
+    /// A PNG or SVG file
+    File(PathBuf),
+    /// A freedesktop icon name (e.g. `battery-caution-symbolic`) looked up in the icon theme
+    Named(String),
+}
+
+impl Icon {
+    /// Classify an `icon` value: file paths end in .png/.svg or start with `/` or `~/`,
+    /// theme icons have a `-` in their name (`battery-low`) or a `theme:` prefix
+    /// (`theme:firefox`), and anything else, such as `OK`, is a glyph.
+    pub fn parse(s: &str) -> Self {
+        let lower = s.to_lowercase();
+        let is_name = |name: &str| {
+            !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        if s.is_empty() {
+            Icon::None
+        } else if lower.ends_with(".png") || lower.ends_with(".svg") || s.starts_with('/') {
+            Icon::File(PathBuf::from(s))
+        } else if let Some(rest) = s.strip_prefix("~/") {
+            Icon::File(dirs::home_dir().map(|h| h.join(rest)).unwrap_or_else(|| PathBuf::from(s)))
+        } else if let Some(name) = s.strip_prefix("theme:").filter(|n| is_name(n)) {
+            Icon::Named(name.to_string())
+        } else if s.contains('-') && !s.starts_with('-') && is_name(s) {
+            Icon::Named(s.to_string())
+        } else {
+            Icon::Glyph(s.to_string())
+        }
+    }
+
+    /// Text for the `{icon}` format placeholder; empty for image icons
+    pub fn glyph(&self) -> &str {
+        match self {
+            Icon::Glyph(g) => g,
+            _ => "",
+        }
+    }
+
+    /// Symbolic icons are monochrome and meant to be recoloured
+    pub fn is_symbolic(&self) -> bool {
+        match self {
+            Icon::Named(name) => name.ends_with("-symbolic"),
+            Icon::File(path) => {
+                path.file_stem().is_some_and(|s| s.to_string_lossy().ends_with("-symbolic"))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What a click on the notification does
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ClickAction {
+    #[default]
+    None,
+    /// Hide the notification, playing its exit animation
+    Dismiss,
+    /// Hide everything at once, without exit animations
+    DismissAll,
+    /// Run a shell command
+    Command(String),
+}
+
+impl ClickAction {
+    /// `dismiss`, `dismiss-all`, `none`, or anything else as a shell command
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "" | "none" => ClickAction::None,
+            "dismiss" | "close" => ClickAction::Dismiss,
+            "dismiss-all" | "close-all" => ClickAction::DismissAll,
+            _ => ClickAction::Command(s.to_string()),
+        }
+    }
+}
+
+/// Mouse interaction with the notification surface
 #[derive(Debug, Clone)]
+pub struct PointerStyle {
+    pub left_click: ClickAction,
+    pub middle_click: ClickAction,
+    pub right_click: ClickAction,
+    /// Keep the notification on screen while the pointer is over it
+    pub hover_pause: bool,
+}
+
+impl Default for PointerStyle {
+    fn default() -> Self {
+        Self {
+            left_click: ClickAction::Dismiss,
+            middle_click: ClickAction::DismissAll,
+            right_click: ClickAction::None,
+            hover_pause: true,
+        }
+    }
+}
+
+/// Style of the percentage bar drawn for signals with `bar = true`
+#[derive(Debug, Clone)]
+pub struct BarStyle {
+    pub width: f64,
+    pub height: f64,
+    /// Fill colour; the signal colour is used when unset
+    pub color: Option<(f64, f64, f64, f64)>,
+    pub track_color: (f64, f64, f64, f64),
+    pub rounded: bool,
+    /// Fill colour at 0% and 100%, interpolated by value when both are set
+    pub low_color: Option<(f64, f64, f64, f64)>,
+    pub high_color: Option<(f64, f64, f64, f64)>,
+}
+
+impl Default for BarStyle {
+    fn default() -> Self {
+        Self {
+            width: 120.0,
+            height: 8.0,
+            color: None,
+            track_color: (1.0, 1.0, 1.0, 0.2),
+            rounded: true,
+            low_color: None,
+            high_color: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Signal {
     pub message: String,
-    pub icon: String,
+    /// The icon, or the first frame of an animated icon
+    pub icon: Icon,
+    /// Frames of an animated icon, shown `icon_interval` seconds each
+    pub icon_frames: Vec<Icon>,
+    pub icon_interval: f64,
     pub icon_size: f64,
+    /// Paint image icons in the signal colour; defaults to on for symbolic icons
+    pub icon_recolor: Option<bool>,
     pub color: (f64, f64, f64, f64),
+    /// Colours by percentage, interpolated in place of `color`
+    pub color_ramp: Option<Vec<ColorStop>>,
     pub threshold: f64,
     pub state_filter: String,
+    /// Idle animation, played while the notification is shown
     pub animation: Animation,
+    /// Played once when the notification appears
+    pub animation_in: Animation,
+    /// Played once before the notification is hidden
+    pub animation_out: Animation,
     pub duration: u64,
     pub sound: Option<PathBuf>,
+    /// Draw a percentage bar after the text
+    pub bar: bool,
+    /// Secondary text for the `body` layout element
+    pub body: Option<String>,
+    /// Replaces the global layout for this signal
+    pub layout: Option<LayoutBox>,
+    /// Background gradient, border and shadow overrides; `Some(None)` turns
+    /// off a global gradient or shadow
+    pub gradient: Option<Option<Gradient>>,
+    pub border_width: Option<f64>,
+    pub border_color: Option<(f64, f64, f64, f64)>,
+    pub shadow: Option<Option<Shadow>>,
+    /// Appearance and placement overrides of the global settings
+    pub bg_color: Option<(f64, f64, f64, f64)>,
+    pub font: Option<String>,
+    pub font_size: Option<f64>,
+    pub border_radius: Option<CornerRadius>,
+    pub anchor: Option<Anchor>,
+}
+
+impl Signal {
+    /// Whether drawing needs the animation timer
+    pub fn is_animated(&self) -> bool {
+        self.animation != Animation::None
+            || self.animation_in != Animation::None
+            || self.has_animated_icon()
+    }
+
+    /// Whether the icon cycles through frames
+    pub fn has_animated_icon(&self) -> bool {
+        self.icon_frames.len() > 1 && self.icon_interval > 0.0
+    }
+
+    /// The icon to show `t` seconds after the notification appeared
+    pub fn icon_at(&self, t: f64) -> &Icon {
+        if !self.has_animated_icon() {
+            return &self.icon;
+        }
+        let frame = (t / self.icon_interval) as usize;
+        &self.icon_frames[frame % self.icon_frames.len()]
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -163,12 +696,38 @@ pub struct AppConfig {
     pub text_color: (f64, f64, f64, f64),
     pub bg_color: (f64, f64, f64, f64),
     pub signals: Vec<Signal>,
-    pub border_radius: f64,
-    pub gradient: bool,
+    pub border_radius: CornerRadius,
+    /// Border stroke drawn inside the background edge; none when 0
+    pub border_width: f64,
+    pub border_color: (f64, f64, f64, f64),
+    pub gradient: Option<Gradient>,
+    pub shadow: Option<Shadow>,
+    /// Text, icon and bar colours by percentage, for signals without their own
+    pub color_ramp: Option<Vec<ColorStop>>,
+    pub bar: BarStyle,
+    pub pointer: PointerStyle,
+    /// Named colours from `[colors]`
+    pub colors: HashMap<String, (f64, f64, f64, f64)>,
+    /// Keyframe animations from `[animations.<name>]`, by name
+    pub animations: HashMap<String, Arc<KeyframeAnimation>>,
+    pub layout: LayoutBox,
+    /// Transparent space above and below the notification for animations
+    pub animation_margin: f64,
+    /// strftime-style format for the `timestamp` layout element
+    pub timestamp_format: String,
     pub format: String,
     pub output: OutputMode,
     pub battery_mode: BatteryMode,
     pub fps: u64,
+    /// Wrap text to this many pixels (no wrapping when unset)
+    pub max_width: Option<f64>,
+    /// Ellipsize wrapped text after this many lines
+    pub max_lines: Option<u32>,
+    pub ellipsize: Ellipsize,
+    /// Interpret Pango markup (`<b>`, `<i>`, `<span foreground=...>`) in messages
+    pub markup: bool,
+    /// Icon theme for named icons; the GTK theme or hicolor when unset
+    pub icon_theme: Option<String>,
     pub config_path: Option<PathBuf>,
 }
 
@@ -183,12 +742,28 @@ impl Default for AppConfig {
             text_color: (1.0, 1.0, 1.0, 1.0),
             bg_color: (0.0, 0.0, 0.0, 0.6),
             signals: Vec::new(),
-            border_radius: 0.0,
-            gradient: false,
+            border_radius: CornerRadius::default(),
+            border_width: 0.0,
+            border_color: (1.0, 1.0, 1.0, 0.3),
+            gradient: None,
+            shadow: None,
+            color_ramp: None,
+            bar: BarStyle::default(),
+            pointer: PointerStyle::default(),
+            colors: HashMap::new(),
+            animations: HashMap::new(),
+            layout: LayoutBox::default(),
+            animation_margin: 60.0,
+            timestamp_format: "%H:%M".to_string(),
             format: "{message} {percent}%".to_string(),
             output: OutputMode::Primary,
             battery_mode: BatteryMode::First,
             fps: 30,
+            max_width: None,
+            max_lines: None,
+            ellipsize: Ellipsize::End,
+            markup: true,
+            icon_theme: None,
             config_path: None,
         }
     }
@@ -210,15 +785,246 @@ fn parse_font_weight(s: &str) -> FontWeight {
 }
 
 fn parse_animation(s: &str) -> Animation {
-    match s.to_lowercase().as_str() {
+    builtin_animation(s).unwrap_or_else(|| {
+        eprintln!("Unknown animation '{}', using none", s);
+        Animation::None
+    })
+}
+
+fn builtin_animation(s: &str) -> Option<Animation> {
+    Some(match s.to_lowercase().as_str() {
         "blink" | "flicker" => Animation::Blink,
         "pulse" => Animation::Pulse,
         "fade" | "fadein" | "fadeout" | "fade-in" | "fade-out" => Animation::Fade,
         "slide" | "slideright" | "slide-right" => Animation::SlideRight,
         "slideleft" | "slide-left" => Animation::SlideLeft,
+        "slideup" | "slide-up" => Animation::SlideUp,
+        "slidedown" | "slide-down" => Animation::SlideDown,
+        "zoom" | "zoomin" | "zoom-in" | "scale" => Animation::Zoom,
+        "shake" => Animation::Shake,
+        "typewriter" | "type" => Animation::Typewriter,
         "bounce" => Animation::Bounce,
-        _ => Animation::None,
+        "" | "none" => Animation::None,
+        _ => return None,
+    })
+}
+
+fn parse_keyframe_animation(
+    name: &str,
+    cfg: &AnimationConfig,
+) -> Result<KeyframeAnimation, ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidAnimation { name: name.to_string(), reason };
+    if cfg.keyframes.is_empty() {
+        return Err(invalid("no keyframes".to_string()));
     }
+    let keyframes =
+        cfg.keyframes
+            .iter()
+            .map(|k| {
+                let easing = match &k.easing {
+                    Some(e) => Easing::parse(e)
+                        .ok_or_else(|| invalid(format!("unknown easing '{}'", e)))?,
+                    None => Easing::Linear,
+                };
+                Ok(Keyframe {
+                    time: k.time.max(0.0),
+                    alpha: k.alpha,
+                    offset_x: k.offset_x,
+                    offset_y: k.offset_y,
+                    scale: k.scale,
+                    rotation: k.rotation,
+                    easing,
+                })
+            })
+            .collect::<Result<Vec<_>, ConfigError>>()?;
+    Ok(KeyframeAnimation::new(name, keyframes, cfg.repeat))
+}
+
+fn parse_ellipsize(s: &str) -> Ellipsize {
+    match s.to_lowercase().as_str() {
+        "none" => Ellipsize::None,
+        "start" => Ellipsize::Start,
+        "middle" => Ellipsize::Middle,
+        _ => Ellipsize::End,
+    }
+}
+
+fn parse_align(s: &str) -> Align {
+    match s.to_lowercase().as_str() {
+        "start" | "left" | "top" => Align::Start,
+        "end" | "right" | "bottom" => Align::End,
+        _ => Align::Center,
+    }
+}
+
+fn parse_element(s: &str) -> Option<Element> {
+    match s.to_lowercase().as_str() {
+        "icon" => Some(Element::Icon),
+        "title" | "text" | "message" => Some(Element::Title),
+        "body" => Some(Element::Body),
+        "bar" => Some(Element::Bar),
+        "timestamp" | "time" => Some(Element::Timestamp),
+        _ => {
+            eprintln!("Unknown layout element '{}', ignoring", s);
+            None
+        }
+    }
+}
+
+fn parse_padding(p: &PaddingConfig) -> Padding {
+    match p {
+        PaddingConfig::All(v) => Padding::all(*v),
+        PaddingConfig::Sides(v) => match v.as_slice() {
+            [all] => Padding::all(*all),
+            [vertical, horizontal] => {
+                Padding { top: *vertical, right: *horizontal, bottom: *vertical, left: *horizontal }
+            }
+            [top, right, bottom, left] => {
+                Padding { top: *top, right: *right, bottom: *bottom, left: *left }
+            }
+            _ => {
+                eprintln!("Invalid layout padding {:?}, expected 1, 2 or 4 values", v);
+                Padding::default()
+            }
+        },
+    }
+}
+
+/// Build a layout box over `base`. Nested boxes start from an empty box that
+/// runs across their parent, with no padding and the parent's spacing.
+fn parse_layout(cfg: &LayoutConfig, base: LayoutBox) -> LayoutBox {
+    let mut layout = base;
+    if let Some(d) = &cfg.direction {
+        layout.direction = match d.to_lowercase().as_str() {
+            "column" | "vertical" => Direction::Column,
+            _ => Direction::Row,
+        };
+    }
+    if let Some(p) = &cfg.padding {
+        layout.padding = parse_padding(p);
+    }
+    if let Some(s) = cfg.spacing {
+        layout.spacing = s;
+    }
+    if let Some(a) = &cfg.align {
+        layout.align = parse_align(a);
+    }
+    if let Some(j) = &cfg.justify {
+        layout.justify = parse_align(j);
+    }
+    layout.min_width = cfg.min_width.or(layout.min_width);
+    layout.max_width = cfg.max_width.or(layout.max_width);
+    layout.width = cfg.width.or(layout.width);
+    layout.height = cfg.height.or(layout.height);
+
+    if let Some(items) = &cfg.items {
+        let child_base = LayoutBox {
+            direction: layout.direction.flipped(),
+            items: Vec::new(),
+            padding: Padding::default(),
+            spacing: layout.spacing,
+            align: layout.align,
+            justify: Align::Start,
+            min_width: None,
+            max_width: None,
+            width: None,
+            height: None,
+        };
+        layout.items = items
+            .iter()
+            .filter_map(|item| match item {
+                LayoutItemConfig::Element(name) => parse_element(name).map(LayoutItem::Element),
+                LayoutItemConfig::Box(b) => {
+                    Some(LayoutItem::Box(parse_layout(b, child_base.clone())))
+                }
+            })
+            .collect();
+    }
+    layout
+}
+
+fn parse_radius(r: &RadiusConfig) -> CornerRadius {
+    match r {
+        RadiusConfig::All(r) => CornerRadius::all(*r),
+        RadiusConfig::Corners([tl, tr, br, bl]) => {
+            CornerRadius { top_left: *tl, top_right: *tr, bottom_right: *br, bottom_left: *bl }
+        }
+    }
+}
+
+/// Resolve a colour value: `[colors]` names first, then CSS colour syntax
+fn resolve_color(
+    value: &ColorValue,
+    colors: &HashMap<String, (f64, f64, f64, f64)>,
+    field: &str,
+) -> Result<(f64, f64, f64, f64), ConfigError> {
+    match value {
+        ColorValue::Rgba(c) => Ok((c[0], c[1], c[2], c[3])),
+        ColorValue::Text(name) => {
+            colors.get(name).copied().or_else(|| color::parse_color(name)).ok_or_else(|| {
+                ConfigError::InvalidColor { field: field.to_string(), value: name.clone() }
+            })
+        }
+    }
+}
+
+/// Resolve the colours of `[position, colour]` pairs
+fn parse_stops(
+    stops: &[(f64, ColorValue)],
+    colors: &HashMap<String, (f64, f64, f64, f64)>,
+    field: &str,
+) -> Result<Vec<ColorStop>, ConfigError> {
+    stops.iter().map(|(o, c)| Ok((*o, resolve_color(c, colors, field)?))).collect()
+}
+
+/// A colour ramp, sorted by percentage
+fn parse_color_ramp(
+    stops: &[(f64, ColorValue)],
+    colors: &HashMap<String, (f64, f64, f64, f64)>,
+    field: &str,
+) -> Result<Vec<ColorStop>, ConfigError> {
+    let mut ramp = parse_stops(stops, colors, field)?;
+    ramp.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(ramp)
+}
+
+fn parse_gradient(
+    g: &GradientConfig,
+    colors: &HashMap<String, (f64, f64, f64, f64)>,
+    field: &str,
+) -> Result<Option<Gradient>, ConfigError> {
+    match g {
+        GradientConfig::Enabled(true) => Ok(Some(Gradient::Darken)),
+        GradientConfig::Enabled(false) => Ok(None),
+        GradientConfig::Stops(spec) => {
+            let stops = parse_stops(&spec.stops, colors, field)?;
+            Ok(match spec.kind.as_deref().map(str::to_lowercase).as_deref() {
+                Some("radial") => Some(Gradient::Radial { stops }),
+                _ => Some(Gradient::Linear { angle: spec.angle.unwrap_or(0.0), stops }),
+            })
+        }
+    }
+}
+
+fn parse_shadow(
+    s: &ShadowConfig,
+    colors: &HashMap<String, (f64, f64, f64, f64)>,
+    field: &str,
+) -> Result<Option<Shadow>, ConfigError> {
+    let default = Shadow::default();
+    let s = match s {
+        ShadowConfig::Enabled(on) => return Ok(on.then_some(default)),
+        ShadowConfig::Spec(spec) => spec,
+    };
+    Ok(Some(Shadow {
+        color: match &s.color {
+            Some(c) => resolve_color(c, colors, field)?,
+            None => default.color,
+        },
+        blur: s.blur.unwrap_or(default.blur),
+        offset_x: s.offset.map(|o| o[0]).unwrap_or(default.offset_x),
+        offset_y: s.offset.map(|o| o[1]).unwrap_or(default.offset_y),
+    }))
 }
 
 fn parse_output_mode(s: &str) -> OutputMode {
@@ -239,9 +1045,9 @@ fn parse_battery_mode(s: &str) -> BatteryMode {
 }
 
 impl AppConfig {
-    pub fn load() -> Self {
-        let mut config = Self::default();
-
+    /// Load the first config found in the search paths, or the defaults if
+    /// there is none
+    pub fn load() -> Result<Self, ConfigError> {
         // Search paths for config files (TOML first, then legacy .conf)
         let search_paths = [
             std::env::current_dir().ok().map(|p| p.join("inno.toml")),
@@ -266,9 +1072,18 @@ impl AppConfig {
 
         let Some(config_path) = loaded_path else {
             eprintln!("No config found!");
-            return config;
+            return Ok(Self::default());
         };
 
+        Self::load_from(&config_path)
+    }
+
+    /// Load config from an explicit path, skipping the search paths. Any
+    /// error fails the whole file rather than applying part of it.
+    pub fn load_from(config_path: &Path) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let config_path = config_path.to_path_buf();
+
         config.config_path = Some(config_path.clone());
         eprintln!("Loading config from: {:?}", config_path);
 
@@ -276,20 +1091,26 @@ impl AppConfig {
         let is_toml = config_path.extension().map(|e| e == "toml").unwrap_or(false);
 
         if is_toml {
-            if let Err(e) = config.load_toml(&config_path) {
-                eprintln!("Failed to parse TOML config: {}", e);
-            }
+            config.load_toml(&config_path)?;
         } else {
-            config.load_legacy(&config_path);
+            config.load_legacy(&config_path)?;
         }
 
-        config
+        Ok(config)
     }
 
     fn load_toml(&mut self, path: &PathBuf) -> Result<(), ConfigError> {
         let content = std::fs::read_to_string(path)?;
         let file: ConfigFile = toml::from_str(&content)?;
 
+        // Named colours, usable everywhere a colour is expected
+        let mut colors = HashMap::new();
+        for (name, value) in &file.colors {
+            let c = resolve_color(value, &HashMap::new(), &format!("colors.{}", name))?;
+            colors.insert(name.clone(), c);
+        }
+        let color = |value: &ColorValue, field: &str| resolve_color(value, &colors, field);
+
         // General settings
         if let Some(general) = file.general {
             if let Some(font) = general.font {
@@ -319,53 +1140,173 @@ impl AppConfig {
             if let Some(fps) = general.fps {
                 self.fps = fps;
             }
+            if let Some(w) = general.max_width {
+                self.max_width = Some(w);
+            }
+            if let Some(lines) = general.max_lines {
+                self.max_lines = Some(lines);
+            }
+            if let Some(e) = general.ellipsize {
+                self.ellipsize = parse_ellipsize(&e);
+            }
+            if let Some(m) = general.markup {
+                self.markup = m;
+            }
+            if let Some(theme) = general.icon_theme {
+                self.icon_theme = Some(theme);
+            }
         }
 
         // Appearance settings
         if let Some(appearance) = file.appearance {
-            if let Some(c) = appearance.text_color {
-                self.text_color = (c[0], c[1], c[2], c[3]);
+            if let Some(c) = &appearance.text_color {
+                self.text_color = color(c, "appearance.text_color")?;
+            }
+            if let Some(c) = &appearance.bg_color {
+                self.bg_color = color(c, "appearance.bg_color")?;
+            }
+            if let Some(r) = &appearance.border_radius {
+                self.border_radius = parse_radius(r);
+            }
+            if let Some(w) = appearance.border_width {
+                self.border_width = w;
+            }
+            if let Some(c) = &appearance.border_color {
+                self.border_color = color(c, "appearance.border_color")?;
+            }
+            if let Some(g) = &appearance.gradient {
+                self.gradient = parse_gradient(g, &colors, "appearance.gradient")?;
+            }
+            if let Some(s) = &appearance.shadow {
+                self.shadow = parse_shadow(s, &colors, "appearance.shadow")?;
+            }
+            if let Some(r) = &appearance.color_ramp {
+                self.color_ramp = Some(parse_color_ramp(r, &colors, "appearance.color_ramp")?);
+            }
+        }
+
+        // Bar settings
+        if let Some(bar) = file.bar {
+            if let Some(w) = bar.width {
+                self.bar.width = w;
+            }
+            if let Some(h) = bar.height {
+                self.bar.height = h;
+            }
+            if let Some(c) = &bar.color {
+                self.bar.color = Some(color(c, "bar.color")?);
+            }
+            if let Some(c) = &bar.track_color {
+                self.bar.track_color = color(c, "bar.track_color")?;
             }
-            if let Some(c) = appearance.bg_color {
-                self.bg_color = (c[0], c[1], c[2], c[3]);
+            if let Some(r) = bar.rounded {
+                self.bar.rounded = r;
             }
-            if let Some(r) = appearance.border_radius {
-                self.border_radius = r;
+            if let Some(c) = &bar.low_color {
+                self.bar.low_color = Some(color(c, "bar.low_color")?);
+            }
+            if let Some(c) = &bar.high_color {
+                self.bar.high_color = Some(color(c, "bar.high_color")?);
+            }
+        }
+
+        if let Some(pointer) = &file.pointer {
+            if let Some(a) = &pointer.left_click {
+                self.pointer.left_click = ClickAction::parse(a);
             }
-            if let Some(g) = appearance.gradient {
-                self.gradient = g;
+            if let Some(a) = &pointer.middle_click {
+                self.pointer.middle_click = ClickAction::parse(a);
             }
+            if let Some(a) = &pointer.right_click {
+                self.pointer.right_click = ClickAction::parse(a);
+            }
+            if let Some(h) = pointer.hover_pause {
+                self.pointer.hover_pause = h;
+            }
+        }
+
+        // Layout
+        if let Some(layout) = &file.layout {
+            self.layout = parse_layout(layout, LayoutBox::default());
+            if let Some(m) = layout.animation_margin {
+                self.animation_margin = m;
+            }
+            if let Some(f) = &layout.timestamp_format {
+                self.timestamp_format = f.clone();
+            }
+        }
+
+        // Keyframe animations, before the signals that use them
+        for (name, anim) in &file.animations {
+            let anim = parse_keyframe_animation(name, anim)?;
+            self.animations.insert(name.clone(), Arc::new(anim));
         }
 
         // Parse signals
         for sig_cfg in file.signal {
-            let color = file
-                .colors
-                .get(&sig_cfg.color)
-                .map(|c| (c[0], c[1], c[2], c[3]))
-                .unwrap_or((1.0, 1.0, 1.0, 1.0));
+            let field = |name: &str| format!("signal '{}' {}", sig_cfg.message, name);
+            let optional_color = |value: &Option<ColorValue>, name: &str| {
+                value.as_ref().map(|c| color(c, &field(name))).transpose()
+            };
+
+            let icon_frames: Vec<Icon> = match &sig_cfg.icon {
+                IconConfig::Single(icon) if icon.is_empty() => Vec::new(),
+                IconConfig::Single(icon) => vec![Icon::parse(icon)],
+                IconConfig::Frames(frames) => frames.iter().map(|f| Icon::parse(f)).collect(),
+            };
 
             let signal = Signal {
-                message: sig_cfg.message,
-                icon: sig_cfg.icon,
+                message: sig_cfg.message.clone(),
+                icon: icon_frames.first().cloned().unwrap_or_default(),
+                icon_frames,
+                icon_interval: sig_cfg.icon_interval.unwrap_or(DEFAULT_ICON_INTERVAL),
                 icon_size: sig_cfg.icon_size.unwrap_or(DEFAULT_ICON_SIZE),
-                color,
+                icon_recolor: sig_cfg.icon_recolor,
+                color: optional_color(&sig_cfg.color, "color")?.unwrap_or(self.text_color),
+                color_ramp: sig_cfg
+                    .color_ramp
+                    .as_ref()
+                    .map(|r| parse_color_ramp(r, &colors, &field("color_ramp")))
+                    .transpose()?,
                 threshold: sig_cfg.threshold,
                 state_filter: sig_cfg.state.to_lowercase(),
-                animation: parse_animation(&sig_cfg.animation),
+                animation: self.find_animation(
+                    sig_cfg.animation_idle.as_deref().unwrap_or(&sig_cfg.animation),
+                ),
+                animation_in: self.find_animation(sig_cfg.animation_in.as_deref().unwrap_or("")),
+                animation_out: self.find_animation(sig_cfg.animation_out.as_deref().unwrap_or("")),
                 duration: sig_cfg.duration.unwrap_or(5),
                 sound: sig_cfg.sound.map(PathBuf::from),
+                bar: sig_cfg.bar,
+                body: sig_cfg.body,
+                layout: sig_cfg.layout.as_ref().map(|l| parse_layout(l, self.layout.clone())),
+                gradient: sig_cfg
+                    .gradient
+                    .as_ref()
+                    .map(|g| parse_gradient(g, &colors, &field("gradient")))
+                    .transpose()?,
+                border_width: sig_cfg.border_width,
+                border_color: optional_color(&sig_cfg.border_color, "border_color")?,
+                shadow: sig_cfg
+                    .shadow
+                    .as_ref()
+                    .map(|s| parse_shadow(s, &colors, &field("shadow")))
+                    .transpose()?,
+                bg_color: optional_color(&sig_cfg.bg_color, "bg_color")?,
+                font: sig_cfg.font,
+                font_size: sig_cfg.font_size,
+                border_radius: sig_cfg.border_radius.as_ref().map(parse_radius),
+                anchor: sig_cfg.position.as_deref().map(Anchor::parse),
             };
             self.signals.push(signal);
         }
 
+        self.colors = colors;
         Ok(())
     }
 
-    fn load_legacy(&mut self, path: &PathBuf) {
-        let Ok(content) = std::fs::read_to_string(path) else {
-            return;
-        };
+    fn load_legacy(&mut self, path: &PathBuf) -> Result<(), ConfigError> {
+        let content = std::fs::read_to_string(path)?;
 
         // First pass: collect colors
         let mut colors: HashMap<String, (f64, f64, f64, f64)> = HashMap::new();
@@ -405,14 +1346,17 @@ impl AppConfig {
                     "font_weight" => self.font_weight = parse_font_weight(value),
                     "position" => self.anchor = Anchor::parse(value),
                     "format" => self.format = value.to_string(),
-                    "border_radius" => self.border_radius = value.parse().unwrap_or(0.0),
+                    "border_radius" => {
+                        self.border_radius = CornerRadius::all(value.parse().unwrap_or(0.0))
+                    }
                     "gradient" => {
-                        self.gradient = value.eq_ignore_ascii_case("true") || value == "1"
+                        let on = value.eq_ignore_ascii_case("true") || value == "1";
+                        self.gradient = on.then_some(Gradient::Darken);
                     }
                     "output" => self.output = parse_output_mode(value),
                     "battery_mode" => self.battery_mode = parse_battery_mode(value),
                     "signal" => {
-                        if let Some(s) = self.parse_legacy_signal(value, &colors) {
+                        if let Some(s) = self.parse_legacy_signal(value, &colors)? {
                             self.signals.push(s);
                         }
                     }
@@ -422,6 +1366,7 @@ impl AppConfig {
                 }
             }
         }
+        Ok(())
     }
 
     fn parse_color(value: &str) -> Option<(f64, f64, f64, f64)> {
@@ -434,29 +1379,62 @@ impl AppConfig {
         &self,
         value: &str,
         colors: &HashMap<String, (f64, f64, f64, f64)>,
-    ) -> Option<Signal> {
+    ) -> Result<Option<Signal>, ConfigError> {
         let parts: Vec<&str> = value.split(',').map(|s| s.trim()).collect();
         if parts.len() < 8 {
-            return None;
+            return Ok(None);
         }
 
         let color = colors
             .get(parts[3])
             .copied()
             .or_else(|| Self::parse_color(parts[3]))
-            .unwrap_or((1.0, 1.0, 1.0, 1.0));
+            .or_else(|| color::parse_color(parts[3]))
+            .ok_or_else(|| ConfigError::InvalidColor {
+                field: format!("signal '{}'", parts[0]),
+                value: parts[3].to_string(),
+            })?;
 
-        Some(Signal {
+        Ok(Some(Signal {
             message: parts[0].to_string(),
-            icon: parts[1].to_string(),
+            icon: Icon::parse(parts[1]),
             icon_size: parts[2].parse().unwrap_or(DEFAULT_ICON_SIZE),
+            icon_recolor: None,
             color,
             threshold: parts[4].parse().unwrap_or(100.0),
             state_filter: parts[5].to_lowercase(),
             animation: parse_animation(parts[6]),
             duration: parts[7].parse().unwrap_or(5),
             sound: parts.get(8).filter(|s| !s.is_empty()).map(|s| PathBuf::from(*s)),
-        })
+            bar: false,
+            ..Signal::default()
+        }))
+    }
+
+    /// Resolve an animation name: `[animations]` tables first, then the built-ins
+    pub fn find_animation(&self, name: &str) -> Animation {
+        match self.animations.get(name) {
+            Some(anim) => Animation::Custom(anim.clone()),
+            None => parse_animation(name),
+        }
+    }
+
+    /// Like `find_animation`, but `None` for unknown names
+    pub fn lookup_animation(&self, name: &str) -> Option<Animation> {
+        match self.animations.get(name) {
+            Some(anim) => Some(Animation::Custom(anim.clone())),
+            None => builtin_animation(name),
+        }
+    }
+
+    /// Resolve a colour string: `[colors]` names first, then CSS colour syntax
+    pub fn lookup_color(&self, s: &str) -> Option<(f64, f64, f64, f64)> {
+        self.colors.get(s).copied().or_else(|| color::parse_color(s))
+    }
+
+    /// Look up a signal by its configured message (e.g. "Low Battery!")
+    pub fn find_signal_by_message(&self, message: &str) -> Option<&Signal> {
+        self.signals.iter().find(|s| s.message == message)
     }
 
     #[allow(dead_code)]
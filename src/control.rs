@@ -1,35 +1,194 @@
 //! DBus control interface for inno
 //!
 //! Exposes org.inno.Control interface on session bus for external control.
-//! Methods: Show(message), Hide, GetState, Reload
+//! Methods: Show(message), ShowEx(options), Update(id, options), Close(id), Hide,
+//! GetState, GetDevices, GetDevice(path), ListEvents, EnableEvent(name),
+//! DisableEvent(name), TriggerEvent(name, values), GetHistory, Reload
+//! Signals: NotificationShown, NotificationHidden, StateChanged
+//! Properties: CurrentNotification, Devices, DoNotDisturb
 
-use tokio::sync::mpsc;
+use futures::StreamExt;
+use inno::notification::{HideReason, Priority, ShowRequest};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use zbus::fdo::{DBusProxy, RequestNameFlags, RequestNameReply};
 use zbus::interface;
+use zbus::object_server::{InterfaceRef, SignalEmitter};
+use zbus::zvariant::{OwnedValue, Value};
 
-/// Control events sent from DBus to main loop
-#[derive(Debug, Clone)]
+/// Well-known name of the control interface
+pub const BUS_NAME: &str = "org.inno.Control";
+
+/// How many shown notifications the history keeps
+const HISTORY_LEN: usize = 50;
+
+/// Control events sent from DBus or the control socket to main loop
+#[derive(Debug)]
 pub enum ControlEvent {
-    /// Show a custom notification message
-    Show { message: String, duration: u64 },
+    /// Show a custom notification message; replies with its id
+    Show { message: String, duration: u64, reply: oneshot::Sender<Result<u32, String>> },
+    /// Show a notification with an icon, colour, animation etc.; replies with its id
+    ShowEx { request: Box<ShowRequest>, reply: oneshot::Sender<Result<u32, String>> },
+    /// Change the fields set in `request` of notification `id` while it is showing
+    Update { id: u32, request: Box<ShowRequest>, reply: oneshot::Sender<Result<(), String>> },
+    /// Hide notification `id` if it is showing
+    Close { id: u32 },
     /// Hide the current notification
     Hide,
-    /// Reload configuration
+    /// List event definitions as (name, enabled, bus)
+    ListEvents { reply: oneshot::Sender<Vec<(String, bool, String)>> },
+    /// Turn an event definition on or off
+    SetEventEnabled { name: String, enabled: bool, reply: oneshot::Sender<Result<(), String>> },
+    /// Act as if an event fired with `values`
+    TriggerEvent {
+        name: String,
+        values: HashMap<String, String>,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Reload configuration and event definitions
     Reload,
+    /// Hide and exit
+    Shutdown,
+}
+
+/// Last known reading of a device
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    /// Name of the event that reported it
+    pub event: String,
+    pub percent: f64,
+    pub state: String,
+    pub name: String,
+    /// UPower says it powers the system, so it is the laptop battery
+    pub power_supply: bool,
+}
+
+/// What the daemon shows and knows, shared by the DBus and socket interfaces
+#[derive(Debug, Default)]
+pub struct Status {
+    /// Id, event and text of the notification on screen; id 0 when none is
+    pub current: (u32, String, String),
+    /// Devices by object path
+    pub devices: BTreeMap<String, Device>,
+    /// Shown notifications as (id, event, text, unix time), oldest first
+    pub history: VecDeque<(u32, String, String, i64)>,
+    /// Only critical notifications are shown while set
+    pub do_not_disturb: bool,
+    /// When the daemon started, in unix seconds
+    pub started: i64,
+    /// The config file in use, if any
+    pub config_path: Option<PathBuf>,
+    /// How the control name and each event bus are doing, by bus
+    pub buses: BTreeMap<String, String>,
+}
+
+impl Status {
+    /// The laptop battery's percentage and state
+    pub fn battery(&self) -> (f64, String) {
+        let battery = self.devices.values().find(|device| device.power_supply);
+        battery.map_or((100.0, "unknown".to_string()), |device| {
+            (device.percent, device.state.clone())
+        })
+    }
+}
+
+pub type SharedStatus = Arc<Mutex<Status>>;
+
+/// Seconds since the unix epoch
+pub fn unix_time() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
+
+/// A change sent to socket subscribers, mirroring the DBus signals
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "signal", rename_all = "kebab-case")]
+pub enum Notice {
+    NotificationShown { id: u32, event: String, text: String },
+    NotificationHidden { id: u32, reason: &'static str },
+    StateChanged { device: String, percent: f64, state: String },
 }
 
 /// DBus control service
 pub struct InnoService {
     pub tx: mpsc::Sender<ControlEvent>,
-    pub battery_percentage: std::sync::Arc<std::sync::atomic::AtomicU32>,
-    pub battery_state: std::sync::Arc<std::sync::RwLock<String>>,
+    status: SharedStatus,
+}
+
+impl InnoService {
+    async fn set_event_enabled(&self, name: String, enabled: bool) -> zbus::fdo::Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ControlEvent::SetEventEnabled { name, enabled, reply })
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        rx.await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?
+            .map_err(zbus::fdo::Error::InvalidArgs)
+    }
+
+    /// A device as (path, event, percent, state, name)
+    fn device_tuple(path: &str, device: &Device) -> (String, String, f64, String, String) {
+        (
+            path.to_string(),
+            device.event.clone(),
+            device.percent,
+            device.state.clone(),
+            device.name.clone(),
+        )
+    }
 }
 
 #[interface(name = "org.inno.Control")]
 impl InnoService {
-    /// Show a custom notification
-    async fn show(&self, message: String, duration: u64) -> zbus::fdo::Result<()> {
+    /// Show a custom notification and return its id
+    async fn show(&self, message: String, duration: u64) -> zbus::fdo::Result<u32> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ControlEvent::Show { message, duration, reply })
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        rx.await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?
+            .map_err(zbus::fdo::Error::Failed)
+    }
+
+    /// Show a notification described by `options` (message, body, icon, color,
+    /// animation, sound, priority, percent, position, signal, duration) and
+    /// return its id
+    async fn show_ex(&self, options: HashMap<String, OwnedValue>) -> zbus::fdo::Result<u32> {
+        let request = parse_show_options(&options)?;
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ControlEvent::ShowEx { request: Box::new(request), reply })
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        rx.await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?
+            .map_err(zbus::fdo::Error::InvalidArgs)
+    }
+
+    /// Update notification `id` in place; only the given options change
+    async fn update(&self, id: u32, options: HashMap<String, OwnedValue>) -> zbus::fdo::Result<()> {
+        let request = parse_show_options(&options)?;
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ControlEvent::Update { id, request: Box::new(request), reply })
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        rx.await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?
+            .map_err(zbus::fdo::Error::InvalidArgs)
+    }
+
+    /// Close notification `id`; does nothing if it is no longer showing
+    async fn close(&self, id: u32) -> zbus::fdo::Result<()> {
         self.tx
-            .send(ControlEvent::Show { message, duration })
+            .send(ControlEvent::Close { id })
             .await
             .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
         Ok(())
@@ -44,14 +203,71 @@ impl InnoService {
         Ok(())
     }
 
-    /// Get current battery state
+    /// Get the laptop battery's percentage and state
     fn get_state(&self) -> zbus::fdo::Result<(f64, String)> {
-        let pct = self.battery_percentage.load(std::sync::atomic::Ordering::Relaxed) as f64 / 100.0;
-        let state = self.battery_state.read().unwrap().clone();
-        Ok((pct * 100.0, state))
+        Ok(self.status.lock().unwrap().battery())
     }
 
-    /// Reload configuration
+    /// All known devices as (path, event, percent, state, name)
+    fn get_devices(&self) -> Vec<(String, String, f64, String, String)> {
+        let status = self.status.lock().unwrap();
+        status.devices.iter().map(|(path, device)| Self::device_tuple(path, device)).collect()
+    }
+
+    /// The device at `path` as (path, event, percent, state, name)
+    fn get_device(&self, path: &str) -> zbus::fdo::Result<(String, String, f64, String, String)> {
+        self.status
+            .lock()
+            .unwrap()
+            .devices
+            .get(path)
+            .map(|device| Self::device_tuple(path, device))
+            .ok_or_else(|| zbus::fdo::Error::UnknownObject(format!("No device '{}'", path)))
+    }
+
+    /// List event definitions as (name, enabled, bus)
+    async fn list_events(&self) -> zbus::fdo::Result<Vec<(String, bool, String)>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ControlEvent::ListEvents { reply })
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        rx.await.map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Start listening for an event
+    async fn enable_event(&self, name: String) -> zbus::fdo::Result<()> {
+        self.set_event_enabled(name, true).await
+    }
+
+    /// Stop listening for an event
+    async fn disable_event(&self, name: String) -> zbus::fdo::Result<()> {
+        self.set_event_enabled(name, false).await
+    }
+
+    /// Handle an event as if it fired; `percentage`, `state`, `path` and the
+    /// event's message placeholders are read from `values`
+    async fn trigger_event(
+        &self,
+        name: String,
+        values: HashMap<String, String>,
+    ) -> zbus::fdo::Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ControlEvent::TriggerEvent { name, values, reply })
+            .await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        rx.await
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?
+            .map_err(zbus::fdo::Error::InvalidArgs)
+    }
+
+    /// Recently shown notifications as (id, event, text, unix time), oldest first
+    fn get_history(&self) -> Vec<(u32, String, String, i64)> {
+        self.status.lock().unwrap().history.iter().cloned().collect()
+    }
+
+    /// Reload configuration and event definitions
     async fn reload(&self) -> zbus::fdo::Result<()> {
         self.tx
             .send(ControlEvent::Reload)
@@ -64,23 +280,299 @@ impl InnoService {
     fn version(&self) -> &str {
         env!("CARGO_PKG_VERSION")
     }
+
+    /// The notification on screen as (id, event, text); id 0 when nothing is shown
+    #[zbus(property)]
+    fn current_notification(&self) -> (u32, String, String) {
+        self.status.lock().unwrap().current.clone()
+    }
+
+    /// Known devices as (path, event, percent, state, name)
+    #[zbus(property)]
+    fn devices(&self) -> Vec<(String, String, f64, String, String)> {
+        self.get_devices()
+    }
+
+    /// Whether only critical notifications are shown
+    #[zbus(property)]
+    fn do_not_disturb(&self) -> bool {
+        self.status.lock().unwrap().do_not_disturb
+    }
+
+    #[zbus(property)]
+    fn set_do_not_disturb(&mut self, enabled: bool) {
+        eprintln!("DBus: Do not disturb {}", if enabled { "on" } else { "off" });
+        self.status.lock().unwrap().do_not_disturb = enabled;
+    }
+
+    /// A notification appeared
+    #[zbus(signal)]
+    async fn notification_shown(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        event: &str,
+        text: &str,
+    ) -> zbus::Result<()>;
+
+    /// A notification went away: expired, dismissed, closed or replaced
+    #[zbus(signal)]
+    async fn notification_hidden(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        reason: &str,
+    ) -> zbus::Result<()>;
+
+    /// A device's percentage or state changed
+    #[zbus(signal)]
+    async fn state_changed(
+        emitter: &SignalEmitter<'_>,
+        device: &str,
+        percent: f64,
+        state: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Records what the main loop shows in the shared status and announces it
+/// as DBus signals and to socket subscribers
+#[derive(Clone)]
+pub struct Announcer {
+    status: SharedStatus,
+    iface: Option<InterfaceRef<InnoService>>,
+    notices: broadcast::Sender<Notice>,
 }
 
-/// Start the DBus control interface
+impl Announcer {
+    /// Without `conn` only the status and socket subscribers are updated
+    pub async fn new(status: SharedStatus, conn: Option<&zbus::Connection>) -> Self {
+        let iface = match conn {
+            Some(conn) => conn.object_server().interface("/org/inno/Control").await.ok(),
+            None => None,
+        };
+        let (notices, _) = broadcast::channel(16);
+        Self { status, iface, notices }
+    }
+
+    pub fn status(&self) -> &SharedStatus {
+        &self.status
+    }
+
+    /// Receive every change from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Notice> {
+        self.notices.subscribe()
+    }
+
+    pub fn do_not_disturb(&self) -> bool {
+        self.status.lock().unwrap().do_not_disturb
+    }
+
+    pub async fn set_do_not_disturb(&self, enabled: bool) {
+        self.status.lock().unwrap().do_not_disturb = enabled;
+        if let Some(iface) = &self.iface {
+            let emitter = iface.signal_emitter();
+            log_error(iface.get().await.do_not_disturb_changed(emitter).await);
+        }
+    }
+
+    /// Notification `id` from `event` is now on screen
+    pub async fn shown(&self, id: u32, event: &str, text: &str) {
+        {
+            let mut status = self.status.lock().unwrap();
+            status.current = (id, event.to_string(), text.to_string());
+            status.history.push_back((id, event.to_string(), text.to_string(), unix_time()));
+            if status.history.len() > HISTORY_LEN {
+                status.history.pop_front();
+            }
+        }
+        let _ = self.notices.send(Notice::NotificationShown {
+            id,
+            event: event.to_string(),
+            text: text.to_string(),
+        });
+        if let Some(iface) = &self.iface {
+            let emitter = iface.signal_emitter();
+            let result = InnoService::notification_shown(emitter, id, event, text).await;
+            log_error(result.and(iface.get().await.current_notification_changed(emitter).await));
+        }
+    }
+
+    /// The text of notification `id` changed in place
+    pub async fn updated(&self, id: u32, text: &str) {
+        {
+            let mut status = self.status.lock().unwrap();
+            if status.current.0 != id {
+                return;
+            }
+            status.current.2 = text.to_string();
+        }
+        if let Some(iface) = &self.iface {
+            let emitter = iface.signal_emitter();
+            log_error(iface.get().await.current_notification_changed(emitter).await);
+        }
+    }
+
+    /// Notification `id` went away
+    pub async fn hidden(&self, id: u32, reason: HideReason) {
+        self.status.lock().unwrap().current = (0, String::new(), String::new());
+        let _ = self.notices.send(Notice::NotificationHidden { id, reason: reason.as_str() });
+        if let Some(iface) = &self.iface {
+            let emitter = iface.signal_emitter();
+            let result = InnoService::notification_hidden(emitter, id, reason.as_str()).await;
+            log_error(result.and(iface.get().await.current_notification_changed(emitter).await));
+        }
+    }
+
+    /// Record a reading of the device at `path`; unknown values keep their last state
+    pub async fn device(
+        &self,
+        path: &str,
+        event: &str,
+        percent: Option<f64>,
+        state: Option<&str>,
+        name: Option<&str>,
+        power_supply: bool,
+    ) {
+        let (percent, state) = {
+            let mut status = self.status.lock().unwrap();
+            let entry = status.devices.entry(path.to_string()).or_insert_with(|| Device {
+                event: event.to_string(),
+                percent: 0.0,
+                state: "unknown".to_string(),
+                // The last path element, e.g. battery_BAT0
+                name: path.rsplit('/').next().unwrap_or(path).to_string(),
+                power_supply,
+            });
+            let updated = Device {
+                event: event.to_string(),
+                percent: percent.unwrap_or(entry.percent),
+                state: state.map_or_else(|| entry.state.clone(), String::from),
+                name: name.map_or_else(|| entry.name.clone(), String::from),
+                power_supply,
+            };
+            if *entry == updated {
+                return;
+            }
+            *entry = updated;
+            (entry.percent, entry.state.clone())
+        };
+        let _ = self.notices.send(Notice::StateChanged {
+            device: path.to_string(),
+            percent,
+            state: state.clone(),
+        });
+        if let Some(iface) = &self.iface {
+            let emitter = iface.signal_emitter();
+            let result = InnoService::state_changed(emitter, path, percent, &state).await;
+            log_error(result.and(iface.get().await.devices_changed(emitter).await));
+        }
+    }
+}
+
+fn log_error(result: zbus::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("DBus: failed to emit signal: {}", e);
+    }
+}
+
+/// A string option
+fn string_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Str(s) => Some(s.to_string()),
+        _ => None,
+    }
+}
+
+/// A numeric option of any integer or floating point type
+fn number_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::U8(n) => Some(*n as f64),
+        Value::I16(n) => Some(*n as f64),
+        Value::U16(n) => Some(*n as f64),
+        Value::I32(n) => Some(*n as f64),
+        Value::U32(n) => Some(*n as f64),
+        Value::I64(n) => Some(*n as f64),
+        Value::U64(n) => Some(*n as f64),
+        Value::F64(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Read `ShowEx` options into a request
+fn parse_show_options(options: &HashMap<String, OwnedValue>) -> zbus::fdo::Result<ShowRequest> {
+    let mut request = ShowRequest::default();
+    for (key, value) in options {
+        let value: &Value = value;
+        let invalid = || zbus::fdo::Error::InvalidArgs(format!("Invalid value for '{}'", key));
+        let string = || string_value(value).ok_or_else(invalid);
+        match key.as_str() {
+            "message" => request.message = Some(string()?),
+            "body" => request.body = Some(string()?),
+            "icon" => request.icon = Some(string()?),
+            "color" => request.color = Some(string()?),
+            "animation" => request.animation = Some(string()?),
+            "sound" => request.sound = Some(string()?),
+            "position" => request.position = Some(string()?),
+            "signal" => request.signal = Some(string()?),
+            "percent" => request.percent = Some(number_value(value).ok_or_else(invalid)?),
+            "duration" => {
+                let secs = number_value(value).filter(|d| *d >= 0.0).ok_or_else(invalid)?;
+                request.duration = Some(secs as u64);
+            }
+            "priority" => {
+                let name =
+                    string_value(value).or_else(|| number_value(value).map(|n| n.to_string()));
+                request.priority =
+                    Some(name.as_deref().and_then(Priority::parse).ok_or_else(invalid)?);
+            }
+            other => {
+                return Err(zbus::fdo::Error::InvalidArgs(format!("Unknown option '{}'", other)));
+            }
+        }
+    }
+    Ok(request)
+}
+
+/// Another daemon owns the bus name and did not hand it over
+#[derive(Debug, Error)]
+#[error("another instance is already running (use --replace to take over)")]
+pub struct AlreadyRunning;
+
+/// Start the DBus control interface. With `replace`, a running daemon is
+/// asked to give up the name; either way this one gives it up to a later
+/// `--replace` and then sends `Shutdown`.
 pub async fn start_control_service(
     tx: mpsc::Sender<ControlEvent>,
-    battery_percentage: std::sync::Arc<std::sync::atomic::AtomicU32>,
-    battery_state: std::sync::Arc<std::sync::RwLock<String>>,
+    status: SharedStatus,
+    replace: bool,
 ) -> anyhow::Result<zbus::Connection> {
     let conn = zbus::Connection::session().await?;
 
-    let service = InnoService { tx, battery_percentage, battery_state };
+    let service = InnoService { tx: tx.clone(), status };
 
     conn.object_server().at("/org/inno/Control", service).await?;
 
-    conn.request_name("org.inno.Control").await?;
+    // Listen before requesting so a quick replacement is not missed
+    let mut name_lost = DBusProxy::new(&conn).await?.receive_name_lost().await?;
+
+    let mut flags = RequestNameFlags::AllowReplacement | RequestNameFlags::DoNotQueue;
+    if replace {
+        flags |= RequestNameFlags::ReplaceExisting;
+    }
+    match conn.request_name_with_flags(BUS_NAME, flags).await? {
+        RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {}
+        RequestNameReply::InQueue | RequestNameReply::Exists => return Err(AlreadyRunning.into()),
+    }
+
+    tokio::spawn(async move {
+        while let Some(lost) = name_lost.next().await {
+            if lost.args().is_ok_and(|args| args.name == BUS_NAME) {
+                eprintln!("DBus: {} taken over by another instance", BUS_NAME);
+                let _ = tx.send(ControlEvent::Shutdown).await;
+                break;
+            }
+        }
+    });
 
-    eprintln!("DBus control interface registered at org.inno.Control");
+    eprintln!("DBus control interface registered at {}", BUS_NAME);
 
     Ok(conn)
 }
@@ -0,0 +1,204 @@
+//! Daemon lifecycle: `inno start|stop|status|restart`
+//!
+//! A running daemon keeps its PID in `$XDG_RUNTIME_DIR/inno/inno.pid`. These
+//! commands find it there and clear the file if that process has gone.
+
+use crate::client::{self, EXIT_NOT_RUNNING, EXIT_USAGE};
+use crate::ipc;
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Subcommands handled by `run`
+pub const COMMANDS: &[&str] = &["start", "stop", "status", "restart"];
+
+/// How long `start` and `stop` wait for the daemon
+const WAIT: Duration = Duration::from_secs(5);
+
+/// Path of the PID file
+pub fn pid_path() -> std::io::Result<PathBuf> {
+    Ok(ipc::runtime_dir()?.join("inno").join("inno.pid"))
+}
+
+/// Holds the PID file while the daemon runs and removes it on drop
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    pub fn create() -> std::io::Result<Self> {
+        let path = pid_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, format!("{}\n", std::process::id()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // A daemon that replaced this one owns the file now
+        if read_pid() == Some(std::process::id() as i32) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_pid() -> Option<i32> {
+    std::fs::read_to_string(pid_path().ok()?).ok()?.trim().parse().ok()
+}
+
+/// Whether `pid` is a live inno, not some other process that got the PID
+fn alive(pid: i32) -> bool {
+    // Signal 0 only checks; EPERM means it exists but belongs to someone else
+    let exists = unsafe { libc::kill(pid, 0) } == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+    let comm = |pid: &str| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok();
+    exists && comm(&pid.to_string()) == comm("self")
+}
+
+/// PID of the running daemon; a PID file left by one that died is removed
+pub fn running_pid() -> Option<i32> {
+    let pid = read_pid()?;
+    if alive(pid) {
+        return Some(pid);
+    }
+    let path = pid_path().ok()?;
+    eprintln!("inno: removing stale PID file {} (pid {})", path.display(), pid);
+    let _ = std::fs::remove_file(path);
+    None
+}
+
+/// Run subcommand `command` with `args` and return the exit code
+pub async fn run(command: &str, args: &[String]) -> i32 {
+    match command {
+        "start" => start(args),
+        "stop" => stop(),
+        "restart" => match stop() {
+            0 => start(args),
+            code => code,
+        },
+        _ => status().await,
+    }
+}
+
+/// Start the daemon with `args` as its options and wait until it is up
+fn start(args: &[String]) -> i32 {
+    let foreground = ["-d", "--debug", "--test", "--test-animations"];
+    if let Some(flag) = args.iter().find(|a| foreground.contains(&a.as_str())) {
+        eprintln!("inno start: {} runs in the foreground; use 'inno {}' instead", flag, flag);
+        return EXIT_USAGE;
+    }
+    // With --replace the new daemon takes over from the running one
+    let old = running_pid();
+    if let Some(pid) = old.filter(|_| !args.iter().any(|a| a == "--replace")) {
+        println!("inno is already running (pid {})", pid);
+        return 0;
+    }
+
+    // Without --debug inno detaches by itself; this is the process that does so
+    let spawned = std::env::current_exe()
+        .and_then(|exe| Command::new(exe).args(args).stdout(Stdio::null()).status());
+    match spawned {
+        Ok(exit) if exit.success() => {}
+        Ok(_) => return 1,
+        Err(e) => {
+            eprintln!("inno start: {}", e);
+            return 1;
+        }
+    }
+
+    // The PID file appears once the surface is up
+    let deadline = Instant::now() + WAIT;
+    while Instant::now() < deadline {
+        if let Some(pid) = running_pid().filter(|&pid| Some(pid) != old) {
+            println!("inno started (pid {})", pid);
+            return 0;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    eprintln!("inno start: the daemon did not come up; run 'inno -d' to see why");
+    1
+}
+
+/// SIGTERM the daemon, then SIGKILL it if it has not exited in time
+fn stop() -> i32 {
+    let Some(pid) = running_pid() else {
+        println!("inno is not running");
+        return 0;
+    };
+    unsafe {
+        libc::kill(pid, libc::SIGTERM);
+    }
+    let deadline = Instant::now() + WAIT;
+    while Instant::now() < deadline {
+        if !alive(pid) {
+            println!("inno stopped");
+            return 0;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    eprintln!("inno stop: pid {} did not exit, killing it", pid);
+    unsafe {
+        libc::kill(pid, libc::SIGKILL);
+    }
+    // SIGKILL can't be caught, but the process may take a moment to go
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while alive(pid) {
+        if Instant::now() >= deadline {
+            eprintln!("inno stop: pid {} is still running", pid);
+            return 1;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    if let Ok(path) = pid_path() {
+        let _ = std::fs::remove_file(path);
+    }
+    println!("inno stopped");
+    0
+}
+
+/// Print the PID, uptime, config, bus health and events; exit code 3 if
+/// nothing is running, as for LSB `status`
+async fn status() -> i32 {
+    let Some(pid) = running_pid() else {
+        println!("inno is not running");
+        return EXIT_NOT_RUNNING;
+    };
+    println!("inno is running (pid {})", pid);
+    let request = json!({ "cmd": "status" });
+    let status = match tokio::time::timeout(WAIT, ipc::request(&request)).await {
+        Ok(Ok(status)) => status,
+        Ok(Err(e)) => {
+            println!("control socket: not responding ({})", e);
+            return 1;
+        }
+        Err(_) => {
+            println!("control socket: no answer within {}s", WAIT.as_secs());
+            return 1;
+        }
+    };
+
+    println!("uptime:  {}", client::duration(status["uptime"].as_i64().unwrap_or(0)));
+    println!("config:  {}", status["config"].as_str().unwrap_or("none, using defaults"));
+    if let Ok(path) = ipc::socket_path() {
+        println!("socket:  {}", path.display());
+    }
+    if let Some(buses) = status["buses"].as_object() {
+        for (bus, health) in buses {
+            println!("{:<8} {}", format!("{}:", bus), health.as_str().unwrap_or_default());
+        }
+    }
+
+    let events = status["events"].as_array().cloned().unwrap_or_default();
+    let enabled = events.iter().filter(|e| e["enabled"] == true).count();
+    println!("events:  {} loaded, {} enabled", events.len(), enabled);
+    for event in &events {
+        let state = if event["enabled"] == true { "enabled" } else { "disabled" };
+        let (name, bus) = (event["name"].as_str(), event["bus"].as_str());
+        println!("  {} ({} bus, {})", name.unwrap_or_default(), bus.unwrap_or_default(), state);
+    }
+    0
+}
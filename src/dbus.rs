@@ -2,11 +2,12 @@
 //!
 //! Listens for DBus signals based on configurable event definitions.
 
+use crate::control::SharedStatus;
 use crate::events::{EventConfig, format_message};
 use futures::StreamExt;
 use std::collections::HashMap;
 use std::time::Instant;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use zbus::Connection;
 use zbus::zvariant::Value;
 
@@ -17,12 +18,15 @@ pub struct NotifyEvent {
     pub path: String,
     #[allow(dead_code)]
     pub message: String,
-    #[allow(dead_code)]
     pub values: HashMap<String, String>,
     /// Percentage for signal matching (if applicable)
     pub percentage: Option<f64>,
     /// State string for signal matching (if applicable)
     pub state: Option<String>,
+    /// Sent by `TriggerEvent`; shown even if nothing changed
+    pub triggered: bool,
+    /// A UPower battery that powers the system, i.e. the laptop battery
+    pub power_supply: bool,
 }
 
 pub enum Event {
@@ -52,6 +56,15 @@ fn extract_u32(val: &Value) -> Option<u32> {
     }
 }
 
+/// Extract bool from a Value
+fn extract_bool(val: &Value) -> Option<bool> {
+    match val {
+        Value::Bool(v) => Some(*v),
+        Value::Value(inner) => extract_bool(inner),
+        _ => None,
+    }
+}
+
 /// Convert Value to String for display
 fn value_to_string(val: &Value, state_map: &HashMap<String, String>) -> String {
     match val {
@@ -116,6 +129,36 @@ async fn query_battery_state(conn: &Connection, path: &str) -> Option<(f64, Stri
     Some((percentage, state))
 }
 
+/// Whether a UPower device is a battery (`Type` 2) that powers the system,
+/// unlike a mouse or headset battery
+async fn query_power_supply(conn: &Connection, path: &str) -> bool {
+    let kind = conn
+        .call_method(
+            Some("org.freedesktop.UPower"),
+            path,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &("org.freedesktop.UPower.Device", "Type"),
+        )
+        .await
+        .ok()
+        .and_then(|reply| reply.body().deserialize::<Value>().ok().and_then(|v| extract_u32(&v)));
+
+    let power_supply = conn
+        .call_method(
+            Some("org.freedesktop.UPower"),
+            path,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &("org.freedesktop.UPower.Device", "PowerSupply"),
+        )
+        .await
+        .ok()
+        .and_then(|reply| reply.body().deserialize::<Value>().ok().and_then(|v| extract_bool(&v)));
+
+    kind == Some(2) && power_supply == Some(true)
+}
+
 /// Query BlueZ device alias (name)
 async fn query_bluez_alias(conn: &Connection, path: &str) -> Option<String> {
     match conn.call_method(
@@ -154,52 +197,98 @@ async fn query_bluez_alias(conn: &Connection, path: &str) -> Option<String> {
     }
 }
 
-/// Run the DBus listener with configurable events
+/// Run the DBus listener with configurable events, restarting it whenever
+/// the event list changes
 pub async fn run_dbus_listener(
     tx: mpsc::Sender<Event>,
-    events: Vec<EventConfig>,
+    mut events: watch::Receiver<Vec<EventConfig>>,
+    status: SharedStatus,
 ) -> anyhow::Result<()> {
-    // Separate events by bus type
-    let system_events: Vec<_> = events.iter().filter(|e| e.bus == "system").collect();
-    let session_events: Vec<_> = events.iter().filter(|e| e.bus == "session").collect();
-
-    eprintln!(
-        "Starting DBus listeners: {} system, {} session events",
-        system_events.len(),
-        session_events.len()
-    );
-
-    // Start system bus listener if we have system events
-    if !system_events.is_empty() {
-        let tx_clone = tx.clone();
-        let events_clone: Vec<EventConfig> = system_events.into_iter().cloned().collect();
-        tokio::spawn(async move {
-            if let Err(e) = run_bus_listener("system", tx_clone, events_clone).await {
-                eprintln!("System bus listener error: {}", e);
-            }
-        });
-    }
+    loop {
+        // The listeners report again once started
+        status.lock().unwrap().buses.retain(|bus, _| bus == "control");
+
+        let enabled: Vec<EventConfig> =
+            events.borrow_and_update().iter().filter(|e| e.enabled).cloned().collect();
+
+        // Separate events by bus type
+        let system_events: Vec<_> = enabled.iter().filter(|e| e.bus == "system").collect();
+        let session_events: Vec<_> = enabled.iter().filter(|e| e.bus == "session").collect();
+
+        eprintln!(
+            "Starting DBus listeners: {} system, {} session events",
+            system_events.len(),
+            session_events.len()
+        );
+
+        let mut listeners = Vec::new();
+
+        // Start system bus listener if we have system events
+        if !system_events.is_empty() {
+            let tx_clone = tx.clone();
+            let events_clone: Vec<EventConfig> = system_events.into_iter().cloned().collect();
+            let status = status.clone();
+            listeners.push(tokio::spawn(async move {
+                let result = run_bus_listener("system", tx_clone, events_clone, &status).await;
+                if let Err(e) = result {
+                    eprintln!("System bus listener error: {}", e);
+                    let health = format!("error: {}", e);
+                    status.lock().unwrap().buses.insert("system".to_string(), health);
+                }
+            }));
+        }
 
-    // Start session bus listener if we have session events
-    if !session_events.is_empty() {
-        let tx_clone = tx.clone();
-        let events_clone: Vec<EventConfig> = session_events.into_iter().cloned().collect();
-        tokio::spawn(async move {
-            if let Err(e) = run_bus_listener("session", tx_clone, events_clone).await {
-                eprintln!("Session bus listener error: {}", e);
-            }
-        });
+        // Start session bus listener if we have session events
+        if !session_events.is_empty() {
+            let tx_clone = tx.clone();
+            let events_clone: Vec<EventConfig> = session_events.into_iter().cloned().collect();
+            let status = status.clone();
+            listeners.push(tokio::spawn(async move {
+                let result = run_bus_listener("session", tx_clone, events_clone, &status).await;
+                if let Err(e) = result {
+                    eprintln!("Session bus listener error: {}", e);
+                    let health = format!("error: {}", e);
+                    status.lock().unwrap().buses.insert("session".to_string(), health);
+                }
+            }));
+        }
+
+        if events.changed().await.is_err() {
+            // Nothing left to reconfigure us
+            return Ok(());
+        }
+
+        // Dropping a listener's connection also drops its match rules
+        eprintln!("Event configuration changed, restarting DBus listeners");
+        for listener in listeners {
+            listener.abort();
+        }
     }
+}
 
-    // Keep the main task alive
-    futures::future::pending::<()>().await;
-    Ok(())
+/// Build an event as if `event` had fired with `values`, for `TriggerEvent`.
+/// `percentage` and `state` feed signal matching; `path` names the device.
+pub fn trigger_event(event: &EventConfig, values: HashMap<String, String>) -> NotifyEvent {
+    let percentage = values.get("percentage").and_then(|p| p.parse().ok());
+    let state = values.get("state").cloned();
+    let path = values.get("path").cloned().unwrap_or_else(|| "/org/inno/Control".to_string());
+    NotifyEvent {
+        event_name: event.name.clone(),
+        path,
+        message: format_message(&event.format.message, &values),
+        values,
+        percentage,
+        state,
+        triggered: true,
+        power_supply: false,
+    }
 }
 
 async fn run_bus_listener(
     bus_type: &str,
     tx: mpsc::Sender<Event>,
     events: Vec<EventConfig>,
+    status: &SharedStatus,
 ) -> anyhow::Result<()> {
     let conn = if bus_type == "system" {
         Connection::system().await?
@@ -222,6 +311,9 @@ async fn run_bus_listener(
         .await?;
     }
 
+    let health = format!("listening for {} events", events.len());
+    status.lock().unwrap().buses.insert(bus_type.to_string(), health);
+
     // Debounce tracking
     let mut last_trigger: HashMap<String, Instant> = HashMap::new();
 
@@ -298,6 +390,7 @@ async fn run_bus_listener(
                 let is_battery_event = path.contains("battery") || path.contains("BAT") || path.contains("headset_dev");
                 let is_bluetooth_event = event.match_rule.arg0.as_deref() == Some("org.bluez.Device1");
 
+                let power_supply = is_battery_event && query_power_supply(&conn, &path).await;
                 let (percentage, state) = if is_battery_event {
                     // Query full battery state from UPower
                     if let Some((pct, st)) = query_battery_state(&conn, &path).await {
@@ -362,6 +455,8 @@ async fn run_bus_listener(
                     values,
                     percentage,
                     state,
+                    triggered: false,
+                    power_supply,
                 };
 
                 if tx.send(Event::Notify(notify_event)).await.is_err() {
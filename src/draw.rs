@@ -1,33 +1,139 @@
-use crate::config::{Animation, AppConfig, Signal};
-use cairo::{Context, LinearGradient};
+use crate::config::{
+    Align, Animation, AppConfig, BarStyle, ColorStop, CornerRadius, Direction, Element, Ellipsize,
+    Gradient, Icon, LayoutBox, LayoutItem, Shadow, Signal,
+};
+use crate::{color, icons};
+use cairo::{Context, FontSlant, FontWeight, LinearGradient, RadialGradient};
+use pangocairo::pango::{self, EllipsizeMode, FontDescription, Layout, Style, Weight, WrapMode};
 use std::f64::consts::PI;
+use std::fs::File;
+use std::path::Path;
 
-const V_PADDING_TOP: f64 = 60.0; // Space for upward animations
-const V_PADDING_BOTTOM: f64 = 60.0; // Space for downward animations
+/// Body text size relative to the main font size
+const BODY_SCALE: f64 = 0.8;
+/// Timestamp size relative to the main font size
+const TIMESTAMP_SCALE: f64 = 0.7;
+
+/// Where a notification is in its enter → idle → exit sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Phase {
+    Enter,
+    #[default]
+    Idle,
+    Exit,
+    /// The exit animation has finished and the surface can be hidden
+    Done,
+}
 
 #[derive(Debug, Clone)]
 pub struct DrawState {
     pub frame: u32,
+    /// Frames since the notification was shown, across all phases
+    pub elapsed: u32,
+    pub phase: Phase,
+    /// Frames since the current enter or exit animation started
+    pub phase_frame: u32,
     pub visible: bool,
     pub alpha: f64,
     pub offset_x: f64,
     pub offset_y: f64,
+    /// Scale and clockwise rotation in degrees, around the notification centre
+    pub scale: f64,
+    pub rotation: f64,
+    /// Number of text characters revealed so far; `None` shows all of them
+    pub reveal: Option<f64>,
 }
 
 impl Default for DrawState {
     fn default() -> Self {
-        Self { frame: 0, visible: true, alpha: 1.0, offset_x: 0.0, offset_y: 0.0 }
+        Self {
+            frame: 0,
+            elapsed: 0,
+            phase: Phase::Idle,
+            phase_frame: 0,
+            visible: true,
+            alpha: 1.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            scale: 1.0,
+            rotation: 0.0,
+            reveal: None,
+        }
     }
 }
 
 impl DrawState {
+    /// Start showing a signal, with its enter animation if it has one
+    pub fn start(&mut self, signal: &Signal) {
+        self.reset();
+        if signal.animation_in != Animation::None {
+            self.phase = Phase::Enter;
+        }
+    }
+
+    /// Begin the signal's exit animation; false if it has none and can be hidden now
+    pub fn start_exit(&mut self, signal: &Signal) -> bool {
+        if signal.animation_out == Animation::None {
+            return false;
+        }
+        if self.phase != Phase::Exit {
+            self.phase = Phase::Exit;
+            self.phase_frame = 0;
+        }
+        true
+    }
+
+    /// Advance one frame through the signal's enter, idle and exit animations
+    pub fn tick_signal(&mut self, signal: &Signal, fps: f64) {
+        self.elapsed = self.elapsed.wrapping_add(1);
+        match self.phase {
+            Phase::Enter => {
+                self.phase_frame += 1;
+                let len = transition_frames(&signal.animation_in, fps);
+                if self.phase_frame < len {
+                    self.apply(&signal.animation_in, self.phase_frame, f64::INFINITY, fps);
+                } else {
+                    self.phase = Phase::Idle;
+                    self.phase_frame = 0;
+                    self.tick(&signal.animation, signal.duration as f64 * fps, fps);
+                }
+            }
+            Phase::Idle => self.tick(&signal.animation, signal.duration as f64 * fps, fps),
+            Phase::Exit => {
+                self.phase_frame += 1;
+                let len = transition_frames(&signal.animation_out, fps);
+                if self.phase_frame < len {
+                    // Built-ins are enter animations, so play them backwards;
+                    // keyframe animations play as written
+                    let frame = match signal.animation_out {
+                        Animation::Custom(_) => self.phase_frame,
+                        _ => len - self.phase_frame,
+                    };
+                    self.apply(&signal.animation_out, frame, f64::INFINITY, fps);
+                } else {
+                    self.phase = Phase::Done;
+                    self.visible = false;
+                }
+            }
+            Phase::Done => {}
+        }
+    }
+
     pub fn tick(&mut self, anim: &Animation, total_frames: f64, fps: f64) {
         self.frame = self.frame.wrapping_add(1);
-        let t = self.frame as f64;
+        self.apply(anim, self.frame, total_frames, fps);
+    }
+
+    /// Set the animated values for `frame` frames into `anim`
+    fn apply(&mut self, anim: &Animation, frame: u32, total_frames: f64, fps: f64) {
+        let t = frame as f64;
+        self.scale = 1.0;
+        self.rotation = 0.0;
+        self.reveal = None;
 
         match anim {
             Animation::Blink => {
-                self.visible = (self.frame / 15) % 2 == 0;
+                self.visible = (frame / 15) % 2 == 0;
                 self.alpha = 1.0;
                 self.offset_x = 0.0;
                 self.offset_y = 0.0;
@@ -74,6 +180,41 @@ impl DrawState {
                 self.offset_x = (1.0 - eased) * 200.0;
                 self.offset_y = 0.0;
             }
+            Animation::SlideUp | Animation::SlideDown => {
+                self.visible = true;
+                self.alpha = 1.0;
+                self.offset_x = 0.0;
+                // Slide in from the bottom or top edge, ease out
+                let progress = (t * 0.05).min(1.0);
+                let eased = 1.0 - (1.0 - progress).powi(3);
+                let direction = if *anim == Animation::SlideUp { 1.0 } else { -1.0 };
+                self.offset_y = direction * (1.0 - eased) * 60.0;
+            }
+            Animation::Zoom => {
+                self.visible = true;
+                self.offset_x = 0.0;
+                self.offset_y = 0.0;
+                // Grow from the centre over 0.3s with a slight overshoot
+                let progress = (t / (fps * 0.3)).min(1.0);
+                self.alpha = progress;
+                self.scale = 1.0 + 2.7 * (progress - 1.0).powi(3) + 1.7 * (progress - 1.0).powi(2);
+            }
+            Animation::Shake => {
+                self.visible = true;
+                self.alpha = 1.0;
+                self.offset_y = 0.0;
+                // Four decaying side-to-side shakes over 0.6s
+                let progress = (t / (fps * 0.6)).min(1.0);
+                self.offset_x = 12.0 * (1.0 - progress) * (progress * PI * 8.0).sin();
+            }
+            Animation::Typewriter => {
+                self.visible = true;
+                self.alpha = 1.0;
+                self.offset_x = 0.0;
+                self.offset_y = 0.0;
+                // 30 characters per second
+                self.reveal = Some(t / fps * 30.0);
+            }
             Animation::Bounce => {
                 self.visible = true;
                 self.alpha = 1.0;
@@ -92,33 +233,410 @@ impl DrawState {
                 self.offset_x = 0.0;
                 self.offset_y = 0.0;
             }
+            Animation::Custom(keyframes) => {
+                let sample = keyframes.sample(t / fps);
+                self.visible = true;
+                self.alpha = sample.alpha.clamp(0.0, 1.0);
+                self.offset_x = sample.offset_x;
+                self.offset_y = sample.offset_y;
+                self.scale = sample.scale.max(0.0);
+                self.rotation = sample.rotation;
+            }
         }
     }
 
     pub fn reset(&mut self) {
-        self.frame = 0;
-        self.visible = true;
-        self.alpha = 1.0;
-        self.offset_x = 0.0;
-        self.offset_y = 0.0;
+        *self = Self::default();
     }
 }
 
+/// Frames one run of an animation takes when used to enter or exit
+fn transition_frames(anim: &Animation, fps: f64) -> u32 {
+    let frames = match anim {
+        Animation::None => 0.0,
+        Animation::Blink => 30.0,
+        Animation::Pulse => PI / 0.15,
+        Animation::Fade | Animation::Bounce => fps * 0.5,
+        Animation::SlideLeft
+        | Animation::SlideRight
+        | Animation::SlideUp
+        | Animation::SlideDown => 20.0,
+        Animation::Zoom => fps * 0.3,
+        Animation::Shake => fps * 0.6,
+        Animation::Typewriter => fps,
+        Animation::Custom(keyframes) => keyframes.duration() * fps,
+    };
+    frames.ceil() as u32
+}
+
 /// Draw a rounded rectangle path
 fn rounded_rect(cr: &Context, x: f64, y: f64, w: f64, h: f64, radius: f64) {
-    let r = radius.min(w / 2.0).min(h / 2.0);
+    rounded_rect_corners(cr, x, y, w, h, &CornerRadius::all(radius));
+}
+
+/// Draw a rectangle path with a separate radius for each corner
+fn rounded_rect_corners(cr: &Context, x: f64, y: f64, w: f64, h: f64, radius: &CornerRadius) {
+    let max = (w / 2.0).min(h / 2.0).max(0.0);
+    let [tl, tr, br, bl] =
+        [radius.top_left, radius.top_right, radius.bottom_right, radius.bottom_left]
+            .map(|r| r.clamp(0.0, max));
     cr.new_sub_path();
-    cr.arc(x + w - r, y + r, r, -PI / 2.0, 0.0);
-    cr.arc(x + w - r, y + h - r, r, 0.0, PI / 2.0);
-    cr.arc(x + r, y + h - r, r, PI / 2.0, PI);
-    cr.arc(x + r, y + r, r, PI, 3.0 * PI / 2.0);
+    cr.arc(x + w - tr, y + tr, tr, -PI / 2.0, 0.0);
+    cr.arc(x + w - br, y + h - br, br, 0.0, PI / 2.0);
+    cr.arc(x + bl, y + h - bl, bl, PI / 2.0, PI);
+    cr.arc(x + tl, y + tl, tl, PI, 3.0 * PI / 2.0);
     cr.close_path();
 }
 
-/// Helper to measure icon extents
-fn measure_icon(cr: &Context, icon: &str, size: f64) -> cairo::TextExtents {
-    cr.set_font_size(size);
-    cr.text_extents(icon).unwrap()
+/// Path of the notification background
+fn background_path(cr: &Context, w: f64, h: f64, radius: &CornerRadius) {
+    if radius.is_zero() {
+        cr.rectangle(0.0, 0.0, w, h);
+    } else {
+        rounded_rect_corners(cr, 0.0, 0.0, w, h, radius);
+    }
+}
+
+/// Set the background source: a gradient or the plain background colour
+fn set_background_source(
+    cr: &Context,
+    gradient: Option<&Gradient>,
+    bg: (f64, f64, f64, f64),
+    w: f64,
+    h: f64,
+    alpha: f64,
+) {
+    let (r, g, b, a) = bg;
+    let add_stops = |pattern: &cairo::Gradient, stops: &[ColorStop]| {
+        for &(offset, (r, g, b, a)) in stops {
+            pattern.add_color_stop_rgba(offset, r, g, b, a * alpha);
+        }
+    };
+    match gradient {
+        None => cr.set_source_rgba(r, g, b, a * alpha),
+        Some(Gradient::Darken) => {
+            // Create an attractive internal visual gradient
+            let gradient = LinearGradient::new(0.0, 0.0, w, 0.0);
+            gradient.add_color_stop_rgba(0.0, r, g, b, a * alpha);
+            gradient.add_color_stop_rgba(1.0, r * 0.7, g * 0.7, b * 0.7, a * alpha * 0.8);
+            cr.set_source(&gradient).unwrap();
+        }
+        Some(Gradient::Linear { angle, stops }) => {
+            // Gradient line through the centre, long enough to reach every corner
+            let (dy, dx) = angle.to_radians().sin_cos();
+            let half = (w / 2.0 * dx).abs() + (h / 2.0 * dy).abs();
+            let (cx, cy) = (w / 2.0, h / 2.0);
+            let gradient =
+                LinearGradient::new(cx - dx * half, cy - dy * half, cx + dx * half, cy + dy * half);
+            add_stops(&gradient, stops);
+            cr.set_source(&gradient).unwrap();
+        }
+        Some(Gradient::Radial { stops }) => {
+            let (cx, cy) = (w / 2.0, h / 2.0);
+            let gradient = RadialGradient::new(cx, cy, 0.0, cx, cy, cx.hypot(cy));
+            add_stops(&gradient, stops);
+            cr.set_source(&gradient).unwrap();
+        }
+    }
+}
+
+/// Space the shadow needs on each side of the background: (left, top, right, bottom)
+fn shadow_extents(shadow: Option<&Shadow>) -> (f64, f64, f64, f64) {
+    let Some(s) = shadow else {
+        return (0.0, 0.0, 0.0, 0.0);
+    };
+    let blur = s.blur.max(0.0).ceil();
+    (
+        (blur - s.offset_x).max(0.0),
+        (blur - s.offset_y).max(0.0),
+        (blur + s.offset_x).max(0.0),
+        (blur + s.offset_y).max(0.0),
+    )
+}
+
+/// One horizontal and one vertical box blur pass over an A8 buffer
+fn box_blur(data: &mut [u8], w: usize, h: usize, stride: usize, radius: usize) {
+    if radius == 0 {
+        return;
+    }
+    let window = (2 * radius + 1) as u32;
+    let mut line = vec![0u8; w.max(h)];
+    for y in 0..h {
+        let row = &mut data[y * stride..y * stride + w];
+        line[..w].copy_from_slice(row);
+        let mut sum: u32 = 0;
+        for x in 0..w + radius {
+            if x < w {
+                sum += line[x] as u32;
+            }
+            if x >= window as usize {
+                sum -= line[x - window as usize] as u32;
+            }
+            if x >= radius {
+                row[x - radius] = (sum / window) as u8;
+            }
+        }
+    }
+    for x in 0..w {
+        for y in 0..h {
+            line[y] = data[y * stride + x];
+        }
+        let mut sum: u32 = 0;
+        for y in 0..h + radius {
+            if y < h {
+                sum += line[y] as u32;
+            }
+            if y >= window as usize {
+                sum -= line[y - window as usize] as u32;
+            }
+            if y >= radius {
+                data[(y - radius) * stride + x] = (sum / window) as u8;
+            }
+        }
+    }
+}
+
+/// Draw a blurred copy of the background shape outside the background itself
+fn draw_shadow(cr: &Context, shadow: &Shadow, w: f64, h: f64, radius: &CornerRadius, alpha: f64) {
+    let pad = shadow.blur.max(0.0).ceil();
+    let (sw, sh) = ((w + 2.0 * pad).ceil() as i32, (h + 2.0 * pad).ceil() as i32);
+    let Ok(mut mask) = cairo::ImageSurface::create(cairo::Format::A8, sw, sh) else {
+        return;
+    };
+    {
+        let Ok(mcr) = Context::new(&mask) else {
+            return;
+        };
+        mcr.translate(pad, pad);
+        background_path(&mcr, w, h, radius);
+        mcr.fill().unwrap();
+    }
+    mask.flush();
+    let stride = mask.stride() as usize;
+    if let Ok(mut data) = mask.data() {
+        // Three box blurs approximate a gaussian
+        let pass = (shadow.blur / 3.0).round() as usize;
+        for _ in 0..3 {
+            box_blur(&mut data, sw as usize, sh as usize, stride, pass);
+        }
+    }
+
+    // Keep the shadow out from under a translucent background
+    cr.save().unwrap();
+    cr.set_fill_rule(cairo::FillRule::EvenOdd);
+    cr.rectangle(
+        -pad + shadow.offset_x.min(0.0),
+        -pad + shadow.offset_y.min(0.0),
+        w + 2.0 * pad + shadow.offset_x.abs(),
+        h + 2.0 * pad + shadow.offset_y.abs(),
+    );
+    background_path(cr, w, h, radius);
+    cr.clip();
+    let (r, g, b, a) = shadow.color;
+    cr.set_source_rgba(r, g, b, a * alpha);
+    cr.mask_surface(&mask, shadow.offset_x - pad, shadow.offset_y - pad).unwrap();
+    cr.restore().unwrap();
+}
+
+/// Build a Pango font description for a font family at a pixel size, with the
+/// configured slant and weight. The family may be a comma-separated list;
+/// fontconfig fills in missing glyphs.
+fn font_description(config: &AppConfig, family: &str, size: f64) -> FontDescription {
+    let mut desc = FontDescription::new();
+    desc.set_family(family);
+    desc.set_absolute_size(size * pango::SCALE as f64);
+    desc.set_style(match config.font_slant {
+        FontSlant::Italic => Style::Italic,
+        FontSlant::Oblique => Style::Oblique,
+        _ => Style::Normal,
+    });
+    desc.set_weight(match config.font_weight {
+        FontWeight::Bold => Weight::Bold,
+        _ => Weight::Normal,
+    });
+    desc
+}
+
+/// Set layout content, falling back to plain text if the markup does not parse
+fn set_layout_text(layout: &Layout, text: &str, markup: bool) {
+    if markup && pango::parse_markup(text, '\0').is_ok() {
+        layout.set_markup(text);
+    } else {
+        layout.set_text(text);
+    }
+}
+
+/// Create a text layout, applying wrapping and ellipsizing. Text wraps at
+/// `max_width` from the config or the space left in its layout box, whichever is smaller.
+fn text_layout(
+    cr: &Context,
+    text: &str,
+    config: &AppConfig,
+    font: &FontDescription,
+    avail_width: Option<f64>,
+) -> Layout {
+    let layout = pangocairo::functions::create_layout(cr);
+    layout.set_font_description(Some(font));
+
+    let max_width = match (config.max_width, avail_width) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    if let Some(max_width) = max_width {
+        layout.set_width((max_width * pango::SCALE as f64) as i32);
+        layout.set_wrap(WrapMode::WordChar);
+        // Negative height limits the number of lines before ellipsizing
+        if let Some(lines) = config.max_lines {
+            layout.set_height(-(lines as i32));
+            layout.set_ellipsize(match config.ellipsize {
+                Ellipsize::None => EllipsizeMode::None,
+                Ellipsize::Start => EllipsizeMode::Start,
+                Ellipsize::Middle => EllipsizeMode::Middle,
+                Ellipsize::End => EllipsizeMode::End,
+            });
+        }
+    }
+
+    set_layout_text(&layout, text, config.markup);
+    layout
+}
+
+/// Create the layout for a signal icon glyph
+fn icon_layout(cr: &Context, icon: &str, font: &FontDescription) -> Layout {
+    let layout = pangocairo::functions::create_layout(cr);
+    layout.set_font_description(Some(font));
+    layout.set_text(icon);
+    layout
+}
+
+/// A measured signal icon, ready to draw
+enum IconView {
+    Glyph { layout: Layout, ink: pango::Rectangle, logical: pango::Rectangle },
+    Image { surface: cairo::ImageSurface, scale: f64, recolor: bool },
+}
+
+impl IconView {
+    fn new(
+        cr: &Context,
+        signal: &Signal,
+        icon: &Icon,
+        config: &AppConfig,
+        family: &str,
+    ) -> Option<Self> {
+        match icon {
+            Icon::None => None,
+            Icon::Glyph(glyph) => {
+                let font = font_description(config, family, signal.icon_size);
+                let layout = icon_layout(cr, glyph, &font);
+                let (ink, logical) = layout.pixel_extents();
+                Some(IconView::Glyph { layout, ink, logical })
+            }
+            icon => {
+                let theme = config.icon_theme.clone().unwrap_or_else(icons::default_theme);
+                // HiDPI buffers carry the output scale as their device scale
+                let scale = cr.target().device_scale().0.round() as u32;
+                let surface = icons::load_icon(icon, signal.icon_size, scale, &theme)?;
+                // PNGs keep their own resolution; scale them to the icon size
+                let scale = signal.icon_size / surface.height().max(1) as f64;
+                let recolor = signal.icon_recolor.unwrap_or_else(|| icon.is_symbolic());
+                Some(IconView::Image { surface, scale, recolor })
+            }
+        }
+    }
+
+    fn width(&self) -> f64 {
+        match self {
+            IconView::Glyph { logical, .. } => logical.width() as f64,
+            IconView::Image { surface, scale, .. } => surface.width() as f64 * scale,
+        }
+    }
+
+    fn height(&self) -> f64 {
+        match self {
+            IconView::Glyph { ink, .. } => ink.height() as f64,
+            IconView::Image { surface, scale, .. } => surface.height() as f64 * scale,
+        }
+    }
+
+    /// Draw with the top-left corner of the icon box at (x, y)
+    fn draw(&self, cr: &Context, x: f64, y: f64, color: (f64, f64, f64, f64), alpha: f64) {
+        let (r, g, b, a) = color;
+        match self {
+            IconView::Glyph { layout, ink, .. } => {
+                // Place by the ink extents, which is what the eye sees
+                cr.set_source_rgba(r, g, b, a * alpha);
+                cr.move_to(x - ink.x() as f64, y - ink.y() as f64);
+                pangocairo::functions::show_layout(cr, layout);
+            }
+            IconView::Image { surface, scale, recolor } => {
+                cr.save().unwrap();
+                cr.translate(x, y);
+                cr.scale(*scale, *scale);
+                if *recolor {
+                    // Use the icon only as a mask for the signal colour
+                    cr.set_source_rgba(r, g, b, a * alpha);
+                    cr.mask_surface(surface, 0.0, 0.0).unwrap();
+                } else {
+                    cr.set_source_surface(surface, 0.0, 0.0).unwrap();
+                    cr.paint_with_alpha(alpha).unwrap();
+                }
+                cr.restore().unwrap();
+            }
+        }
+    }
+}
+
+/// Fill colour for a bar at `fraction` (0.0-1.0): interpolated between the
+/// low and high colours when both are set, else the bar or signal colour.
+fn bar_fill_color(
+    style: &BarStyle,
+    fraction: f64,
+    fallback: (f64, f64, f64, f64),
+) -> (f64, f64, f64, f64) {
+    match (style.low_color, style.high_color) {
+        (Some(lo), Some(hi)) => (
+            lo.0 + (hi.0 - lo.0) * fraction,
+            lo.1 + (hi.1 - lo.1) * fraction,
+            lo.2 + (hi.2 - lo.2) * fraction,
+            lo.3 + (hi.3 - lo.3) * fraction,
+        ),
+        _ => style.color.unwrap_or(fallback),
+    }
+}
+
+/// Draw a horizontal percentage bar with its top-left corner at (x, y)
+fn draw_bar(
+    cr: &Context,
+    x: f64,
+    y: f64,
+    style: &BarStyle,
+    fraction: f64,
+    fill: (f64, f64, f64, f64),
+    alpha: f64,
+) {
+    let radius = if style.rounded { style.height / 2.0 } else { 0.0 };
+
+    // Track
+    let (r, g, b, a) = style.track_color;
+    cr.set_source_rgba(r, g, b, a * alpha);
+    rounded_rect(cr, x, y, style.width, style.height, radius);
+    cr.fill().unwrap();
+
+    // Fill, clipped to the track so rounded ends stay round at low values
+    let (r, g, b, a) = fill;
+    cr.save().unwrap();
+    rounded_rect(cr, x, y, style.width, style.height, radius);
+    cr.clip();
+    cr.set_source_rgba(r, g, b, a * alpha);
+    cr.rectangle(x, y, style.width * fraction, style.height);
+    cr.fill().unwrap();
+    cr.restore().unwrap();
+}
+
+/// Escape text from external sources (DBus values, device names) so it is
+/// shown literally when it is substituted into a markup message.
+pub fn escape_markup(text: &str) -> String {
+    pangocairo::glib::markup_escape_text(text).to_string()
 }
 
 /// Format notification text using config format string
@@ -137,18 +655,304 @@ pub fn format_text(format: &str, icon: &str, message: &str, percent: Option<f64>
     res
 }
 
+/// Everything the layout elements can show for one notification
+struct Content<'a> {
+    text: &'a str,
+    config: &'a AppConfig,
+    /// Font family and size after signal overrides
+    font: &'a str,
+    font_size: f64,
+    signal: Option<&'a Signal>,
+    percent: Option<f64>,
+    bar_fraction: Option<f64>,
+    /// Characters of each text shown by the typewriter animation
+    reveal: Option<usize>,
+    /// Seconds since the notification was shown, for animated icons
+    elapsed: f64,
+}
+
+/// A measured layout node, ready to be placed
+enum Node {
+    Icon(IconView),
+    Text { layout: Layout, logical: pango::Rectangle, alpha: f64 },
+    Bar(f64),
+    Box { layout: LayoutBox, children: Vec<Sized> },
+}
+
+struct Sized {
+    node: Node,
+    w: f64,
+    h: f64,
+}
+
+fn is_text(item: &LayoutItem) -> bool {
+    matches!(item, LayoutItem::Element(Element::Title | Element::Body | Element::Timestamp))
+}
+
+fn measure_text(
+    cr: &Context,
+    content: &Content,
+    text: &str,
+    scale: f64,
+    alpha: f64,
+    avail_width: Option<f64>,
+) -> Option<Sized> {
+    if text.is_empty() {
+        return None;
+    }
+    let config = content.config;
+    let font = font_description(config, content.font, content.font_size * scale);
+    let layout = text_layout(cr, text, config, &font, avail_width);
+    let (_, logical) = layout.pixel_extents();
+    Some(Sized {
+        w: logical.width() as f64,
+        h: logical.height() as f64,
+        node: Node::Text { layout, logical, alpha },
+    })
+}
+
+fn measure_element(
+    cr: &Context,
+    content: &Content,
+    element: Element,
+    avail_width: Option<f64>,
+) -> Option<Sized> {
+    match element {
+        Element::Icon => {
+            let signal = content.signal?;
+            let icon = signal.icon_at(content.elapsed);
+            let icon = IconView::new(cr, signal, icon, content.config, content.font)?;
+            Some(Sized { w: icon.width(), h: icon.height(), node: Node::Icon(icon) })
+        }
+        Element::Title => measure_text(cr, content, content.text, 1.0, 1.0, avail_width),
+        Element::Body => {
+            let signal = content.signal?;
+            let body = format_text(
+                signal.body.as_deref()?,
+                signal.icon.glyph(),
+                &signal.message,
+                content.percent,
+            );
+            measure_text(cr, content, &body, BODY_SCALE, 1.0, avail_width)
+        }
+        Element::Timestamp => {
+            let now = pangocairo::glib::DateTime::now_local().ok()?;
+            let time = now.format(&content.config.timestamp_format).ok()?;
+            measure_text(cr, content, &time, TIMESTAMP_SCALE, 0.7, avail_width)
+        }
+        Element::Bar => {
+            let fraction = content.bar_fraction?;
+            let style = &content.config.bar;
+            Some(Sized { w: style.width, h: style.height, node: Node::Bar(fraction) })
+        }
+    }
+}
+
+fn measure_item(
+    cr: &Context,
+    content: &Content,
+    item: &LayoutItem,
+    avail_width: Option<f64>,
+) -> Option<Sized> {
+    match item {
+        LayoutItem::Element(e) => measure_element(cr, content, *e, avail_width),
+        LayoutItem::Box(b) => measure_box(cr, content, b, avail_width),
+    }
+}
+
+/// Measure a box and its children. `avail_width` is the most the box may
+/// take; text inside wraps to fit. Empty boxes are skipped like empty elements.
+fn measure_box(
+    cr: &Context,
+    content: &Content,
+    layout: &LayoutBox,
+    avail_width: Option<f64>,
+) -> Option<Sized> {
+    let pad = layout.padding;
+    let limit =
+        [layout.width, layout.max_width, avail_width].into_iter().flatten().reduce(f64::min);
+    let inner = limit.map(|w| (w - pad.left - pad.right).max(0.0));
+
+    let mut children: Vec<Option<Sized>> = Vec::with_capacity(layout.items.len());
+    match layout.direction {
+        Direction::Column => {
+            for item in &layout.items {
+                children.push(measure_item(cr, content, item, inner));
+            }
+        }
+        Direction::Row => {
+            // Fixed-size items first, then share what is left between the texts
+            for item in &layout.items {
+                let measured =
+                    if is_text(item) { None } else { measure_item(cr, content, item, inner) };
+                children.push(measured);
+            }
+            let text_count = layout.items.iter().filter(|i| is_text(i)).count();
+            let text_width = inner.map(|inner| {
+                let used: f64 = children.iter().flatten().map(|c| c.w + layout.spacing).sum();
+                let gaps = layout.spacing * text_count.saturating_sub(1) as f64;
+                ((inner - used - gaps) / text_count.max(1) as f64).max(0.0)
+            });
+            for (i, item) in layout.items.iter().enumerate() {
+                if is_text(item) {
+                    children[i] = measure_item(cr, content, item, text_width);
+                }
+            }
+        }
+    }
+    let children: Vec<Sized> = children.into_iter().flatten().collect();
+    if children.is_empty() {
+        return None;
+    }
+
+    let gaps = layout.spacing * (children.len() - 1) as f64;
+    let (content_w, content_h) = match layout.direction {
+        Direction::Row => (
+            children.iter().map(|c| c.w).sum::<f64>() + gaps,
+            children.iter().map(|c| c.h).fold(0.0, f64::max),
+        ),
+        Direction::Column => (
+            children.iter().map(|c| c.w).fold(0.0, f64::max),
+            children.iter().map(|c| c.h).sum::<f64>() + gaps,
+        ),
+    };
+
+    let mut w = content_w + pad.left + pad.right;
+    if let Some(min) = layout.min_width {
+        w = w.max(min);
+    }
+    if let Some(max) = layout.max_width {
+        w = w.min(max);
+    }
+    let w = layout.width.unwrap_or(w);
+    let h = layout.height.unwrap_or(content_h + pad.top + pad.bottom);
+
+    Some(Sized { node: Node::Box { layout: layout.clone(), children }, w, h })
+}
+
+fn align_offset(align: Align, free: f64) -> f64 {
+    match align {
+        Align::Start => 0.0,
+        Align::Center => free / 2.0,
+        Align::End => free,
+    }
+}
+
+/// Make all but the first `chars` characters transparent without changing the layout size
+fn hide_text_after(layout: &Layout, chars: usize) {
+    let text = layout.text();
+    let Some((start, _)) = text.char_indices().nth(chars) else {
+        return;
+    };
+    // Copy: pango ignores setting a list equal to the current one
+    let attrs = layout.attributes().and_then(|a| a.copy()).unwrap_or_default();
+    // An alpha of 0 means "unset" to pango
+    let mut hidden = pango::AttrInt::new_foreground_alpha(1);
+    hidden.set_start_index(start as u32);
+    hidden.set_end_index(text.len() as u32);
+    attrs.insert(hidden);
+    layout.set_attributes(Some(&attrs));
+}
+
+/// Draw a measured node with its top-left corner at (x, y)
+fn draw_node(
+    cr: &Context,
+    sized: &Sized,
+    x: f64,
+    y: f64,
+    content: &Content,
+    color: (f64, f64, f64, f64),
+    alpha: f64,
+) {
+    let (r, g, b, a) = color;
+    match &sized.node {
+        Node::Icon(icon) => icon.draw(cr, x, y, color, alpha),
+        Node::Text { layout, logical, alpha: text_alpha } => {
+            if let Some(chars) = content.reveal {
+                hide_text_after(layout, chars);
+            }
+            cr.set_source_rgba(r, g, b, a * alpha * text_alpha);
+            cr.move_to(x, y - logical.y() as f64);
+            pangocairo::functions::show_layout(cr, layout);
+        }
+        Node::Bar(fraction) => {
+            let style = &content.config.bar;
+            let fill = bar_fill_color(style, *fraction, color);
+            draw_bar(cr, x, y, style, *fraction, fill, alpha);
+        }
+        Node::Box { layout, children } => {
+            let pad = layout.padding;
+            let inner_w = sized.w - pad.left - pad.right;
+            let inner_h = sized.h - pad.top - pad.bottom;
+            let gaps = layout.spacing * children.len().saturating_sub(1) as f64;
+            let main_len = match layout.direction {
+                Direction::Row => children.iter().map(|c| c.w).sum::<f64>() + gaps,
+                Direction::Column => children.iter().map(|c| c.h).sum::<f64>() + gaps,
+            };
+
+            let (x0, y0) = (x + pad.left, y + pad.top);
+            let mut pos = match layout.direction {
+                Direction::Row => align_offset(layout.justify, inner_w - main_len),
+                Direction::Column => align_offset(layout.justify, inner_h - main_len),
+            };
+            for child in children {
+                match layout.direction {
+                    Direction::Row => {
+                        let cy = y0 + align_offset(layout.align, inner_h - child.h);
+                        draw_node(cr, child, x0 + pos, cy, content, color, alpha);
+                        pos += child.w + layout.spacing;
+                    }
+                    Direction::Column => {
+                        let cx = x0 + align_offset(layout.align, inner_w - child.w);
+                        draw_node(cr, child, cx, y0 + pos, content, color, alpha);
+                        pos += child.h + layout.spacing;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The background rectangle `(x, y, w, h)` inside a `w` x `h` surface drawn by
+/// `draw_with_signal`; the rest is shadow and animation margin
+pub fn background_rect(
+    config: &AppConfig,
+    signal: Option<&Signal>,
+    state: &DrawState,
+    w: i32,
+    h: i32,
+) -> (i32, i32, i32, i32) {
+    let shadow = signal.and_then(|s| s.shadow.as_ref()).unwrap_or(&config.shadow).as_ref();
+    let (sl, st, sr, sb) = shadow_extents(shadow);
+    let margin_top = config.animation_margin.max(st);
+    let margin_bottom = config.animation_margin.max(sb);
+    (
+        (sl + state.offset_x).round() as i32,
+        (margin_top + state.offset_y).round() as i32,
+        (w as f64 - sl - sr) as i32,
+        (h as f64 - margin_top - margin_bottom) as i32,
+    )
+}
+
 pub fn draw_with_signal(
     cr: &Context,
     text: &str,
     config: &AppConfig,
     signal: Option<&Signal>,
+    percent: Option<f64>,
     state: &DrawState,
 ) -> (i32, i32) {
-    let (r_bg, g_bg, b_bg, a_bg) = config.bg_color;
-    let (r, g, b, a) = signal.map(|s| s.color).unwrap_or(config.text_color);
+    let ramp = signal.map_or(config.color_ramp.as_ref(), |s| {
+        s.color_ramp.as_ref().or(config.color_ramp.as_ref())
+    });
+    let color = ramp
+        .zip(percent)
+        .and_then(|(ramp, pct)| color::ramp_color(ramp, pct))
+        .or(signal.map(|s| s.color))
+        .unwrap_or(config.text_color);
 
-    // Blink off - return minimal size
-    if signal.is_some_and(|s| s.animation == Animation::Blink && !state.visible) {
+    // Blink off or exit finished - return minimal size
+    if signal.is_some() && !state.visible {
         cr.set_source_rgba(0.0, 0.0, 0.0, 0.0);
         cr.set_operator(cairo::Operator::Source);
         cr.paint().unwrap();
@@ -157,24 +961,38 @@ pub fn draw_with_signal(
 
     let alpha = state.alpha;
 
-    // Select font and measure icon
-    cr.select_font_face(&config.font, config.font_slant, config.font_weight);
+    // Percentage bar, for signals that enable it
+    let bar_fraction = signal.filter(|s| s.bar).and(percent).map(|p| (p / 100.0).clamp(0.0, 1.0));
+    let font = signal.and_then(|s| s.font.as_deref()).unwrap_or(&config.font);
+    let font_size = signal.and_then(|s| s.font_size).unwrap_or(config.font_size);
+    let reveal = state.reveal.map(|chars| chars as usize);
+    let elapsed = state.elapsed as f64 / config.fps as f64;
+    let content =
+        Content { text, config, font, font_size, signal, percent, bar_fraction, reveal, elapsed };
 
-    let mut icon_w = 0.0;
-    if let Some(s) = signal {
-        if !s.icon.is_empty() {
-            let icon_ext = measure_icon(cr, &s.icon, s.icon_size);
-            icon_w = icon_ext.x_advance() + 10.0;
-        }
-    }
-
-    // Measure main text
-    cr.set_font_size(config.font_size);
-    let ext = cr.text_extents(text).unwrap();
+    // Measure the layout tree
+    let layout = signal.and_then(|s| s.layout.as_ref()).unwrap_or(&config.layout);
+    let root = measure_box(cr, &content, layout, None).unwrap_or(Sized {
+        node: Node::Box { layout: layout.clone(), children: Vec::new() },
+        w: layout.width.unwrap_or(layout.padding.left + layout.padding.right),
+        h: layout.height.unwrap_or(layout.padding.top + layout.padding.bottom),
+    });
+    let gradient = signal.and_then(|s| s.gradient.as_ref()).unwrap_or(&config.gradient).as_ref();
+    let shadow = signal.and_then(|s| s.shadow.as_ref()).unwrap_or(&config.shadow).as_ref();
+    let border_width = signal.and_then(|s| s.border_width).unwrap_or(config.border_width);
+    let border_color = signal.and_then(|s| s.border_color).unwrap_or(config.border_color);
+    let bg_color = signal.and_then(|s| s.bg_color).unwrap_or(config.bg_color);
+    let radius = signal.and_then(|s| s.border_radius.as_ref()).unwrap_or(&config.border_radius);
 
-    let w = ext.width().ceil() as i32 + 20 + icon_w as i32;
-    let h_content = ext.height().ceil() as f64 + 20.0;
-    let h = (h_content + V_PADDING_TOP + V_PADDING_BOTTOM) as i32;
+    // The shadow gets its own room at the sides; above and below it shares
+    // the animation margin
+    let (sl, st, sr, sb) = shadow_extents(shadow);
+    let margin_top = config.animation_margin.max(st);
+    let margin_bottom = config.animation_margin.max(sb);
+    let bg_w = root.w.ceil();
+    let h_content = root.h.ceil();
+    let w = (bg_w + sl + sr) as i32;
+    let h = (h_content + margin_top + margin_bottom) as i32;
 
     // Clear canvas
     cr.set_source_rgba(0.0, 0.0, 0.0, 0.0);
@@ -182,52 +1000,90 @@ pub fn draw_with_signal(
     cr.paint().unwrap();
 
     // Apply animation offsets and padding
-    cr.translate(state.offset_x, state.offset_y + V_PADDING_TOP);
-
-    // Draw background (with optional gradient and rounded corners)
+    cr.translate(state.offset_x + sl, state.offset_y + margin_top);
     cr.set_operator(cairo::Operator::Over);
 
-    if config.gradient {
-        // Create an attractive internal visual gradient
-        let gradient = LinearGradient::new(0.0, 0.0, w as f64, 0.0);
-        gradient.add_color_stop_rgba(0.0, r_bg, g_bg, b_bg, a_bg * alpha);
-        gradient.add_color_stop_rgba(1.0, r_bg * 0.7, g_bg * 0.7, b_bg * 0.7, a_bg * alpha * 0.8);
-        cr.set_source(&gradient).unwrap();
-    } else {
-        cr.set_source_rgba(r_bg, g_bg, b_bg, a_bg * alpha);
+    // Scale and rotate around the centre of the notification. Nothing is
+    // visible at zero scale, and cairo cannot invert that matrix.
+    if state.scale < 0.001 {
+        return (w, h);
+    }
+    if state.scale != 1.0 || state.rotation != 0.0 {
+        let (cx, cy) = (bg_w / 2.0, h_content / 2.0);
+        cr.translate(cx, cy);
+        cr.rotate(state.rotation.to_radians());
+        cr.scale(state.scale, state.scale);
+        cr.translate(-cx, -cy);
     }
 
-    if config.border_radius > 0.0 {
-        rounded_rect(cr, 0.0, 0.0, w as f64, h_content, config.border_radius);
-        cr.fill().unwrap();
-    } else {
-        cr.rectangle(0.0, 0.0, w as f64, h_content);
-        cr.fill().unwrap();
-    }
-
-    // Draw icon
-    let text_x = if let Some(s) = signal {
-        if !s.icon.is_empty() {
-            let icon_ext = measure_icon(cr, &s.icon, s.icon_size);
-            cr.set_source_rgba(r, g, b, a * alpha);
-            cr.move_to(
-                10.0 - icon_ext.x_bearing(),
-                h_content / 2.0 - (icon_ext.height() / 2.0 + icon_ext.y_bearing()),
-            );
-            cr.show_text(&s.icon).unwrap();
-            cr.set_font_size(config.font_size);
-            10.0 + icon_w
-        } else {
-            10.0
-        }
-    } else {
-        10.0
-    };
+    if let Some(shadow) = shadow {
+        draw_shadow(cr, shadow, bg_w, h_content, radius, alpha);
+    }
 
-    // Draw text
-    cr.set_source_rgba(r, g, b, a * alpha);
-    cr.move_to(text_x, h_content / 2.0 - (ext.height() / 2.0 + ext.y_bearing()));
-    cr.show_text(text).unwrap();
+    // Draw background (with optional gradient and rounded corners)
+    set_background_source(cr, gradient, bg_color, bg_w, h_content, alpha);
+    background_path(cr, bg_w, h_content, radius);
+    cr.fill().unwrap();
+
+    // Border, kept inside the background edge
+    if border_width > 0.0 {
+        let inset = border_width / 2.0;
+        let inner = CornerRadius {
+            top_left: radius.top_left - inset,
+            top_right: radius.top_right - inset,
+            bottom_right: radius.bottom_right - inset,
+            bottom_left: radius.bottom_left - inset,
+        };
+        cr.save().unwrap();
+        cr.translate(inset, inset);
+        background_path(cr, bg_w - border_width, h_content - border_width, &inner);
+        cr.restore().unwrap();
+        let (r, g, b, a) = border_color;
+        cr.set_source_rgba(r, g, b, a * alpha);
+        cr.set_line_width(border_width);
+        cr.stroke().unwrap();
+    }
+
+    // Draw content
+    draw_node(cr, &root, 0.0, 0.0, &content, color, alpha);
 
     (w, h)
 }
+
+/// Render a notification onto an offscreen image surface sized to fit it.
+/// Mirrors `LayerApp::draw_text_with_signal`: measure first, then draw.
+pub fn render_to_surface(
+    text: &str,
+    config: &AppConfig,
+    signal: Option<&Signal>,
+    percent: Option<f64>,
+    state: &DrawState,
+) -> anyhow::Result<cairo::ImageSurface> {
+    let dummy = cairo::ImageSurface::create(cairo::Format::ARgb32, 1, 1)?;
+    let (w, h) = draw_with_signal(&Context::new(&dummy)?, text, config, signal, percent, state);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h)?;
+    {
+        let cr = Context::new(&surface)?;
+        draw_with_signal(&cr, text, config, signal, percent, state);
+    }
+    surface.flush();
+
+    Ok(surface)
+}
+
+/// Render a notification and write it to a PNG file, without a Wayland connection.
+/// Returns the size of the written image.
+pub fn render_png(
+    path: &Path,
+    text: &str,
+    config: &AppConfig,
+    signal: Option<&Signal>,
+    percent: Option<f64>,
+    state: &DrawState,
+) -> anyhow::Result<(i32, i32)> {
+    let surface = render_to_surface(text, config, signal, percent, state)?;
+    let mut file = File::create(path)?;
+    surface.write_to_png(&mut file)?;
+    Ok((surface.width(), surface.height()))
+}
@@ -136,7 +136,7 @@ fn default_debounce() -> u64 {
     0
 }
 
-/// Load all event configs from the events directory
+/// Load all event configs from the events directory, disabled ones included
 pub fn load_events() -> Vec<EventConfig> {
     let mut events = Vec::new();
 
@@ -163,10 +163,10 @@ pub fn load_events() -> Vec<EventConfig> {
                                         event.name,
                                         path.display()
                                     );
-                                    events.push(event);
                                 } else {
-                                    eprintln!("  Skipped disabled event: {}", event.name);
+                                    eprintln!("  Loaded disabled event: {}", event.name);
                                 }
+                                events.push(event);
                             }
                             Err(e) => {
                                 eprintln!("  Failed to load {:?}: {}", path, e);
@@ -179,7 +179,7 @@ pub fn load_events() -> Vec<EventConfig> {
         }
     }
 
-    if events.is_empty() {
+    if !events.iter().any(|e| e.enabled) {
         eprintln!("No event configs found, using built-in battery event");
         events.push(builtin_battery_event());
     }
@@ -0,0 +1,346 @@
+//! Image icons for signals
+//!
+//! Resolves freedesktop icon names through the XDG icon theme lookup spec
+//! and loads PNG/SVG files into cairo surfaces at the requested size.
+
+use crate::config::Icon;
+use cairo::{Format, ImageSurface};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const EXTENSIONS: [&str; 2] = ["png", "svg"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DirType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+/// A sized subdirectory from a theme's index.theme
+#[derive(Debug, Clone)]
+struct ThemeDir {
+    path: String,
+    size: u32,
+    scale: u32,
+    kind: DirType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDir {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirType::Fixed => self.size == size,
+            DirType::Scalable => self.min_size <= size && size <= self.max_size,
+            DirType::Threshold => {
+                self.size.saturating_sub(self.threshold) <= size
+                    && size <= self.size + self.threshold
+            }
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+        let (min, max) = match self.kind {
+            DirType::Fixed => (self.size, self.size),
+            DirType::Scalable => (self.min_size, self.max_size),
+            DirType::Threshold => {
+                (self.size.saturating_sub(self.threshold), self.size + self.threshold)
+            }
+        };
+        if wanted < min * self.scale {
+            min * self.scale - wanted
+        } else {
+            wanted.saturating_sub(max * self.scale)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Theme {
+    dirs: Vec<ThemeDir>,
+    parents: Vec<String>,
+}
+
+/// Parse an index.theme file into its sized directories and parent themes
+fn parse_index_theme(content: &str) -> Theme {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name.to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    let Some(header) = sections.get("Icon Theme") else {
+        return Theme::default();
+    };
+    let list = |key: &str| -> Vec<String> {
+        header
+            .get(key)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    };
+
+    let mut dirs = Vec::new();
+    for path in list("Directories").into_iter().chain(list("ScaledDirectories")) {
+        let Some(section) = sections.get(&path) else {
+            continue;
+        };
+        let num = |key: &str| section.get(key).and_then(|v| v.parse::<u32>().ok());
+        let Some(size) = num("Size") else {
+            continue;
+        };
+        let kind = match section.get("Type").map(String::as_str) {
+            Some("Fixed") => DirType::Fixed,
+            Some("Scalable") => DirType::Scalable,
+            _ => DirType::Threshold,
+        };
+        dirs.push(ThemeDir {
+            path,
+            size,
+            scale: num("Scale").unwrap_or(1),
+            kind,
+            min_size: num("MinSize").unwrap_or(size),
+            max_size: num("MaxSize").unwrap_or(size),
+            threshold: num("Threshold").unwrap_or(2),
+        });
+    }
+
+    Theme { dirs, parents: list("Inherits") }
+}
+
+/// Icon base directories in lookup order: ~/.icons, $XDG_DATA_HOME/icons,
+/// $XDG_DATA_DIRS/icons and finally /usr/share/pixmaps
+pub fn icon_base_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".icons"));
+    }
+    if let Some(data) = dirs::data_dir() {
+        dirs.push(data.join("icons"));
+    }
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    for dir in data_dirs.split(':').filter(|d| !d.is_empty()) {
+        dirs.push(Path::new(dir).join("icons"));
+    }
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs
+}
+
+/// The user's icon theme from GTK settings, falling back to hicolor
+pub fn default_theme() -> String {
+    dirs::config_dir()
+        .and_then(|dir| std::fs::read_to_string(dir.join("gtk-3.0/settings.ini")).ok())
+        .and_then(|content| {
+            content.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "gtk-icon-theme-name").then(|| value.trim().to_string())
+            })
+        })
+        .unwrap_or_else(|| "hicolor".to_string())
+}
+
+fn load_theme(name: &str, base_dirs: &[PathBuf]) -> Option<Theme> {
+    base_dirs.iter().find_map(|base| {
+        let content = std::fs::read_to_string(base.join(name).join("index.theme")).ok()?;
+        Some(parse_index_theme(&content))
+    })
+}
+
+/// Find `icon` in one theme subdirectory, across all base directories
+fn find_in_dir(
+    icon: &str,
+    theme_name: &str,
+    dir: &ThemeDir,
+    base_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    base_dirs.iter().find_map(|base| {
+        EXTENSIONS
+            .iter()
+            .map(|ext| base.join(theme_name).join(&dir.path).join(format!("{}.{}", icon, ext)))
+            .find(|p| p.is_file())
+    })
+}
+
+fn lookup_icon(
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme_name: &str,
+    theme: &Theme,
+    base_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    // Exact size match first
+    for dir in theme.dirs.iter().filter(|d| d.matches_size(size, scale)) {
+        if let Some(path) = find_in_dir(icon, theme_name, dir, base_dirs) {
+            return Some(path);
+        }
+    }
+
+    // Otherwise the closest size available
+    let mut best: Option<(u32, PathBuf)> = None;
+    for dir in &theme.dirs {
+        let distance = dir.size_distance(size, scale);
+        if best.as_ref().is_some_and(|(d, _)| *d <= distance) {
+            continue;
+        }
+        if let Some(path) = find_in_dir(icon, theme_name, dir, base_dirs) {
+            best = Some((distance, path));
+        }
+    }
+    best.map(|(_, path)| path)
+}
+
+fn find_icon_helper(
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme_name: &str,
+    base_dirs: &[PathBuf],
+    visited: &mut HashSet<String>,
+) -> Option<PathBuf> {
+    if !visited.insert(theme_name.to_string()) {
+        return None;
+    }
+    let theme = load_theme(theme_name, base_dirs)?;
+    if let Some(path) = lookup_icon(icon, size, scale, theme_name, &theme, base_dirs) {
+        return Some(path);
+    }
+    theme
+        .parents
+        .iter()
+        .find_map(|parent| find_icon_helper(icon, size, scale, parent, base_dirs, visited))
+}
+
+/// Resolve an icon name in `theme` (then its parents, then hicolor, then as an
+/// unthemed file directly in a base directory) following the XDG icon theme spec.
+pub fn find_icon_in(
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme: &str,
+    base_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    let mut visited = HashSet::new();
+    find_icon_helper(icon, size, scale, theme, base_dirs, &mut visited)
+        .or_else(|| find_icon_helper(icon, size, scale, "hicolor", base_dirs, &mut visited))
+        .or_else(|| {
+            base_dirs.iter().find_map(|dir| {
+                EXTENSIONS
+                    .iter()
+                    .map(|ext| dir.join(format!("{}.{}", icon, ext)))
+                    .find(|p| p.is_file())
+            })
+        })
+}
+
+/// Resolve an icon name using the standard icon base directories
+pub fn find_icon(icon: &str, size: u32, scale: u32, theme: &str) -> Option<PathBuf> {
+    find_icon_in(icon, size, scale, theme, &icon_base_dirs())
+}
+
+/// Convert a premultiplied RGBA pixmap into a cairo ARGB32 surface
+fn pixmap_to_surface(pixmap: &resvg::tiny_skia::Pixmap) -> Option<ImageSurface> {
+    let (w, h) = (pixmap.width() as i32, pixmap.height() as i32);
+    let mut surface = ImageSurface::create(Format::ARgb32, w, h).ok()?;
+    let stride = surface.stride() as usize;
+    {
+        let mut data = surface.data().ok()?;
+        for (y, row) in pixmap.data().chunks_exact(w as usize * 4).enumerate() {
+            for (x, px) in row.chunks_exact(4).enumerate() {
+                let argb = u32::from_be_bytes([px[3], px[0], px[1], px[2]]);
+                let offset = y * stride + x * 4;
+                data[offset..offset + 4].copy_from_slice(&argb.to_ne_bytes());
+            }
+        }
+    }
+    surface.mark_dirty();
+    Some(surface)
+}
+
+/// Rasterize an SVG so that it fits a `size` x `size` box
+fn load_svg(path: &Path, size: f64) -> Option<ImageSurface> {
+    let data = std::fs::read(path).ok()?;
+    let tree = resvg::usvg::Tree::from_data(&data, &resvg::usvg::Options::default()).ok()?;
+    let svg_size = tree.size();
+    let scale = size as f32 / svg_size.width().max(svg_size.height());
+    let w = (svg_size.width() * scale).ceil().max(1.0) as u32;
+    let h = (svg_size.height() * scale).ceil().max(1.0) as u32;
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(w, h)?;
+    let transform = resvg::tiny_skia::Transform::from_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    pixmap_to_surface(&pixmap)
+}
+
+/// Load a PNG or SVG icon file. SVGs are rendered at `size`; PNGs keep their
+/// own resolution and are scaled when drawn.
+pub fn load_icon_file(path: &Path, size: f64) -> Option<ImageSurface> {
+    let is_svg = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        load_svg(path, size)
+    } else {
+        let mut file = std::fs::File::open(path).ok()?;
+        ImageSurface::create_from_png(&mut file).ok()
+    }
+}
+
+/// A loaded icon's identity: icon, size, output scale and theme
+type CacheKey = (Icon, u32, u32, String);
+
+thread_local! {
+    /// Loaded icons by (icon, size, scale, theme). Failed lookups are cached as
+    /// `None` so a missing icon is only searched for (and logged) once.
+    static ICON_CACHE: RefCell<HashMap<CacheKey, Option<ImageSurface>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Forget loaded icons, so a changed theme or edited file is picked up
+pub fn clear_cache() {
+    ICON_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+/// Load a file or themed icon `size` logical pixels big for an output with
+/// `scale`, caching the result since icons are drawn on every animation frame.
+pub fn load_icon(icon: &Icon, size: f64, scale: u32, theme: &str) -> Option<ImageSurface> {
+    let px = size.round().max(1.0) as u32;
+    let scale = scale.max(1);
+    let key = (icon.clone(), px, scale, theme.to_string());
+    if let Some(cached) = ICON_CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return cached;
+    }
+
+    let device_size = size * scale as f64;
+    let surface = match icon {
+        Icon::File(path) => load_icon_file(path, device_size),
+        Icon::Named(name) => {
+            find_icon(name, px, scale, theme).and_then(|p| load_icon_file(&p, device_size))
+        }
+        Icon::None | Icon::Glyph(_) => None,
+    };
+    if surface.is_none() {
+        eprintln!("Failed to load icon {:?} at {}px x{} (theme '{}')", icon, px, scale, theme);
+    }
+
+    ICON_CACHE.with(|cache| cache.borrow_mut().insert(key, surface.clone()));
+    surface
+}
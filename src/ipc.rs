@@ -0,0 +1,312 @@
+//! Control socket for inno
+//!
+//! A JSON-lines protocol on `$XDG_RUNTIME_DIR/inno.sock` (`/tmp/inno-$UID`
+//! without a runtime dir) mirroring the DBus control interface, so scripts
+//! can use it without DBus:
+//!
+//! ```sh
+//! echo '{"cmd":"show","message":"Hello"}' | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/inno.sock
+//! ```
+//!
+//! Every request line gets one response line, `{"ok":true,...}` or
+//! `{"ok":false,"error":"..."}`. After `subscribe` the connection receives
+//! a line per notification shown or hidden and per device change.
+
+use crate::control::{AlreadyRunning, Announcer, ControlEvent, unix_time};
+use inno::notification::ShowRequest;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, oneshot};
+
+/// Directory for the socket and PID file: `$XDG_RUNTIME_DIR`, or else
+/// `/tmp/inno-$UID` readable by this user only
+pub fn runtime_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir);
+    }
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("inno-{}", uid));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    // Another user could have made it first to catch our requests
+    let meta = std::fs::symlink_metadata(&dir)?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        let error = format!("{} is not a private directory of this user", dir.display());
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, error));
+    }
+    Ok(dir)
+}
+
+/// Path of the control socket
+pub fn socket_path() -> io::Result<PathBuf> {
+    Ok(runtime_dir()?.join("inno.sock"))
+}
+
+/// Whether a daemon is listening on the socket
+pub fn is_running() -> bool {
+    socket_path().is_ok_and(|path| std::os::unix::net::UnixStream::connect(path).is_ok())
+}
+
+/// Send one request to the running daemon and return its response
+pub async fn request(request: &Value) -> anyhow::Result<Value> {
+    let stream = UnixStream::connect(socket_path()?).await?;
+    let (read, mut write) = stream.into_split();
+    send_line(&mut write, request).await?;
+    let Some(line) = BufReader::new(read).lines().next_line().await? else {
+        anyhow::bail!("the daemon closed the connection");
+    };
+    let response: Value = serde_json::from_str(&line)?;
+    if response["ok"] != true {
+        anyhow::bail!("{}", response["error"].as_str().unwrap_or("unknown error"));
+    }
+    Ok(response)
+}
+
+/// Removes the socket on drop, unless another daemon has replaced it since
+pub struct SocketGuard {
+    path: PathBuf,
+    inode: u64,
+}
+
+impl Drop for SocketGuard {
+    fn drop(&mut self) {
+        if std::fs::metadata(&self.path).is_ok_and(|m| m.ino() == self.inode) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Listen on the control socket. With `replace`, a daemon already listening
+/// is asked to shut down; otherwise it is an `AlreadyRunning` error.
+pub fn start(
+    tx: mpsc::Sender<ControlEvent>,
+    announcer: Announcer,
+    replace: bool,
+) -> anyhow::Result<SocketGuard> {
+    let path = socket_path()?;
+    if let Ok(mut stream) = std::os::unix::net::UnixStream::connect(&path) {
+        if !replace {
+            return Err(AlreadyRunning.into());
+        }
+        let _ = stream.write_all(b"{\"cmd\":\"shutdown\"}\n");
+    }
+    // Left over from a daemon that did not exit cleanly, or the one replaced
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    let inode = std::fs::metadata(&path)?.ino();
+    eprintln!("Control socket listening at {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_client(stream, tx.clone(), announcer.clone()));
+                }
+                Err(e) => eprintln!("Control socket accept error: {}", e),
+            }
+        }
+    });
+
+    Ok(SocketGuard { path, inode })
+}
+
+async fn handle_client(stream: UnixStream, tx: mpsc::Sender<ControlEvent>, announcer: Announcer) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = match serde_json::from_str::<Map<String, Value>>(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = json!({ "ok": false, "error": e.to_string() });
+                if send_line(&mut write, &error).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+        if request.get("cmd").and_then(Value::as_str) == Some("subscribe") {
+            subscribe(write, &announcer).await;
+            return;
+        }
+        let response = handle_request(request, &tx, &announcer)
+            .await
+            .unwrap_or_else(|e| json!({ "ok": false, "error": e }));
+        if send_line(&mut write, &response).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn send_line(
+    write: &mut OwnedWriteHalf,
+    value: &impl serde::Serialize,
+) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(value).map_err(std::io::Error::other)?;
+    line.push('\n');
+    write.write_all(line.as_bytes()).await
+}
+
+/// Stream notices until the client goes away
+async fn subscribe(mut write: OwnedWriteHalf, announcer: &Announcer) {
+    let mut notices = announcer.subscribe();
+    if send_line(&mut write, &json!({ "ok": true })).await.is_err() {
+        return;
+    }
+    loop {
+        match notices.recv().await {
+            Ok(notice) => {
+                if send_line(&mut write, &notice).await.is_err() {
+                    return;
+                }
+            }
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Send an event that replies and wait for the reply
+async fn call<T>(
+    tx: &mpsc::Sender<ControlEvent>,
+    event: impl FnOnce(oneshot::Sender<T>) -> ControlEvent,
+) -> Result<T, String> {
+    let (reply, rx) = oneshot::channel();
+    send(tx, event(reply)).await?;
+    rx.await.map_err(|e| e.to_string())
+}
+
+async fn send(tx: &mpsc::Sender<ControlEvent>, event: ControlEvent) -> Result<(), String> {
+    tx.send(event).await.map_err(|e| e.to_string())
+}
+
+/// Remove and deserialize field `key`
+fn take<T: for<'de> Deserialize<'de>>(
+    request: &mut Map<String, Value>,
+    key: &str,
+) -> Result<T, String> {
+    let value = request.remove(key).ok_or_else(|| format!("missing \"{}\"", key))?;
+    serde_json::from_value(value).map_err(|e| format!("invalid \"{}\": {}", key, e))
+}
+
+/// The remaining fields as `show` options
+fn show_request(request: Map<String, Value>) -> Result<Box<ShowRequest>, String> {
+    serde_json::from_value(Value::Object(request)).map_err(|e| e.to_string())
+}
+
+async fn list_events(tx: &mpsc::Sender<ControlEvent>) -> Result<Vec<Value>, String> {
+    let events = call(tx, |reply| ControlEvent::ListEvents { reply }).await?;
+    Ok(events
+        .into_iter()
+        .map(|(name, enabled, bus)| json!({ "name": name, "enabled": enabled, "bus": bus }))
+        .collect())
+}
+
+async fn handle_request(
+    mut request: Map<String, Value>,
+    tx: &mpsc::Sender<ControlEvent>,
+    announcer: &Announcer,
+) -> Result<Value, String> {
+    let cmd: String = take(&mut request, "cmd")?;
+    match cmd.as_str() {
+        "show" => {
+            let request = show_request(request)?;
+            let id = call(tx, |reply| ControlEvent::ShowEx { request, reply }).await??;
+            return Ok(json!({ "ok": true, "id": id }));
+        }
+        "update" => {
+            let id = take(&mut request, "id")?;
+            let request = show_request(request)?;
+            call(tx, |reply| ControlEvent::Update { id, request, reply }).await??;
+        }
+        "close" => send(tx, ControlEvent::Close { id: take(&mut request, "id")? }).await?,
+        "hide" => send(tx, ControlEvent::Hide).await?,
+        "reload" => send(tx, ControlEvent::Reload).await?,
+        "shutdown" => send(tx, ControlEvent::Shutdown).await?,
+        "state" => {
+            let status = announcer.status().lock().unwrap();
+            let (percent, state) = status.battery();
+            let devices: Vec<Value> = status
+                .devices
+                .iter()
+                .map(|(path, device)| {
+                    json!({
+                        "path": path,
+                        "event": device.event,
+                        "percent": device.percent,
+                        "state": device.state,
+                        "name": device.name,
+                    })
+                })
+                .collect();
+            return Ok(
+                json!({ "ok": true, "percent": percent, "state": state, "devices": devices }),
+            );
+        }
+        "history" => {
+            let status = announcer.status().lock().unwrap();
+            let history: Vec<Value> = status
+                .history
+                .iter()
+                .map(|(id, event, text, time)| {
+                    json!({ "id": id, "event": event, "text": text, "time": time })
+                })
+                .collect();
+            return Ok(json!({ "ok": true, "history": history }));
+        }
+        "dnd" => {
+            // Without "enabled" this only reports the setting
+            if request.contains_key("enabled") {
+                announcer.set_do_not_disturb(take(&mut request, "enabled")?).await;
+            }
+            return Ok(json!({ "ok": true, "enabled": announcer.do_not_disturb() }));
+        }
+        "list-events" => {
+            let events = list_events(tx).await?;
+            return Ok(json!({ "ok": true, "events": events }));
+        }
+        "status" => {
+            let events = list_events(tx).await?;
+            let status = announcer.status().lock().unwrap();
+            return Ok(json!({
+                "ok": true,
+                "pid": std::process::id(),
+                "uptime": unix_time() - status.started,
+                "config": status.config_path,
+                "events": events,
+                "buses": status.buses,
+            }));
+        }
+        "enable-event" | "disable-event" => {
+            let name = take(&mut request, "name")?;
+            let enabled = cmd == "enable-event";
+            call(tx, |reply| ControlEvent::SetEventEnabled { name, enabled, reply }).await??;
+        }
+        "trigger-event" => {
+            let name = take(&mut request, "name")?;
+            let values: HashMap<String, String> = if request.contains_key("values") {
+                take(&mut request, "values")?
+            } else {
+                HashMap::new()
+            };
+            call(tx, |reply| ControlEvent::TriggerEvent { name, values, reply }).await??;
+        }
+        other => return Err(format!("unknown command '{}'", other)),
+    }
+    Ok(json!({ "ok": true }))
+}
@@ -1,20 +1,26 @@
 use crate::config::AppConfig;
 use crate::config::Signal;
+use crate::config::{self, HAnchor, VAnchor};
 use crate::draw;
 use crate::draw::DrawState;
 use smithay_client_toolkit::{
-    compositor::{CompositorHandler, CompositorState},
-    delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_seat,
-    delegate_shm,
+    compositor::{CompositorHandler, CompositorState, Region},
+    delegate_compositor, delegate_layer, delegate_output, delegate_pointer, delegate_registry,
+    delegate_seat, delegate_shm,
     output::{OutputHandler, OutputState},
     reexports::client::{
         Connection, QueueHandle,
         globals::registry_queue_init,
-        protocol::{wl_output, wl_seat, wl_shm, wl_surface},
+        protocol::{wl_output, wl_pointer, wl_seat, wl_shm, wl_surface},
     },
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
-    seat::{Capability, SeatHandler, SeatState},
+    seat::{
+        Capability, SeatHandler, SeatState,
+        pointer::{
+            BTN_LEFT, BTN_MIDDLE, BTN_RIGHT, PointerEvent, PointerEventKind, PointerHandler,
+        },
+    },
     shell::{
         WaylandSurface,
         wlr_layer::{
@@ -25,6 +31,16 @@ use smithay_client_toolkit::{
     shm::{Shm, ShmHandler, slot::SlotPool},
 };
 
+/// Pointer input on the notification, handled by the main loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerAction {
+    LeftClick,
+    MiddleClick,
+    RightClick,
+    Enter,
+    Leave,
+}
+
 pub struct LayerApp {
     pub registry_state: RegistryState,
     pub seat_state: SeatState,
@@ -35,10 +51,17 @@ pub struct LayerApp {
 
     pub width: u32,
     pub height: u32,
+    /// Output scale; buffers are this many times the surface size
+    pub scale: i32,
     pub layer_surface: Option<LayerSurface>,
     pub pool: Option<SlotPool>,
     pub exit: bool,
     pub configured: bool,
+    /// Anchor currently set on the layer surface
+    pub anchor: Option<config::Anchor>,
+    pub pointer: Option<wl_pointer::WlPointer>,
+    /// Pointer input since the main loop last looked
+    pub pointer_actions: Vec<PointerAction>,
 }
 
 impl LayerApp {
@@ -60,16 +83,18 @@ impl LayerApp {
             layer_shell,
             width: 0,
             height: 0,
+            scale: 1,
             layer_surface: None,
             pool: None,
             exit: false,
             configured: false,
+            anchor: None,
+            pointer: None,
+            pointer_actions: Vec::new(),
         })
     }
 
     pub fn create_surface(&mut self, qh: &QueueHandle<Self>, config: &AppConfig) {
-        use crate::config::{HAnchor, VAnchor};
-
         if self.layer_surface.is_some() {
             return;
         }
@@ -83,37 +108,63 @@ impl LayerApp {
             None,
         );
 
+        layer.set_keyboard_interactivity(KeyboardInteractivity::None);
+        layer.set_size(1, 1);
+        self.layer_surface = Some(layer);
+        self.set_input_region(None);
+        self.set_anchor(&config.anchor);
+
+        if let Some(layer) = &self.layer_surface {
+            layer.commit();
+        }
+    }
+
+    /// Move the surface to `anchor`; takes effect with the next commit
+    pub fn set_anchor(&mut self, anchor: &config::Anchor) {
+        let Some(layer) = &self.layer_surface else {
+            return;
+        };
+        if self.anchor.as_ref() == Some(anchor) {
+            return;
+        }
+
         // Build anchor flags from config
-        let mut anchor = Anchor::empty();
-        match config.anchor.h {
-            HAnchor::Left => anchor |= Anchor::LEFT,
-            HAnchor::Right => anchor |= Anchor::RIGHT,
+        let mut flags = Anchor::empty();
+        match anchor.h {
+            HAnchor::Left => flags |= Anchor::LEFT,
+            HAnchor::Right => flags |= Anchor::RIGHT,
             HAnchor::Center => {} // no horizontal anchor = centered
         }
-        match config.anchor.v {
-            VAnchor::Top => anchor |= Anchor::TOP,
-            VAnchor::Bottom => anchor |= Anchor::BOTTOM,
+        match anchor.v {
+            VAnchor::Top => flags |= Anchor::TOP,
+            VAnchor::Bottom => flags |= Anchor::BOTTOM,
             VAnchor::Center => {} // no vertical anchor = centered
         }
 
-        layer.set_anchor(anchor);
-        layer.set_margin(
-            config.anchor.margin_v,
-            config.anchor.margin_h,
-            config.anchor.margin_v,
-            config.anchor.margin_h,
-        );
-        layer.set_keyboard_interactivity(KeyboardInteractivity::None);
-        layer.set_size(1, 1);
-        layer.commit();
+        layer.set_anchor(flags);
+        layer.set_margin(anchor.margin_v, anchor.margin_h, anchor.margin_v, anchor.margin_h);
+        self.anchor = Some(anchor.clone());
+    }
 
-        self.layer_surface = Some(layer);
+    /// Accept pointer input only inside `rect`, or nowhere; takes effect with the next commit
+    fn set_input_region(&self, rect: Option<(i32, i32, i32, i32)>) {
+        let Some(layer) = &self.layer_surface else {
+            return;
+        };
+        let Ok(region) = Region::new(&self.compositor_state) else {
+            return;
+        };
+        if let Some((x, y, w, h)) = rect {
+            region.add(x, y, w, h);
+        }
+        // The compositor copies the region, so it can be dropped right away
+        layer.wl_surface().set_input_region(Some(region.wl_region()));
     }
 
     /// Draw text without signal (for DBus Show command)
     pub fn draw_text(&mut self, text: &str, config: &AppConfig) {
         let draw_state = DrawState::default();
-        self.draw_text_with_signal(text, config, None, &draw_state);
+        self.draw_text_with_signal(text, config, None, None, &draw_state);
     }
 
     pub fn draw_text_with_signal(
@@ -121,15 +172,21 @@ impl LayerApp {
         text: &str,
         config: &AppConfig,
         signal: Option<&Signal>,
+        percent: Option<f64>,
         draw_state: &DrawState,
     ) {
         if self.layer_surface.is_none() || !self.configured {
             return;
         }
 
+        // Signals may sit somewhere other than the configured position
+        self.set_anchor(signal.and_then(|s| s.anchor.as_ref()).unwrap_or(&config.anchor));
+
+        let scale = self.scale.max(1);
         let dummy = cairo::ImageSurface::create(cairo::Format::ARgb32, 1, 1).unwrap();
+        dummy.set_device_scale(scale as f64, scale as f64);
         let cr = cairo::Context::new(&dummy).unwrap();
-        let (w, h) = draw::draw_with_signal(&cr, text, config, signal, draw_state);
+        let (w, h) = draw::draw_with_signal(&cr, text, config, signal, percent, draw_state);
 
         // Skip drawing if animation says not visible
         if w <= 1 || h <= 1 {
@@ -142,6 +199,7 @@ impl LayerApp {
                         for i in canvas.iter_mut() {
                             *i = 0;
                         }
+                        layer.wl_surface().set_buffer_scale(1);
                         layer.wl_surface().attach(Some(buffer.wl_buffer()), 0, 0);
                         layer.wl_surface().damage(0, 0, 1, 1);
                         layer.commit();
@@ -154,26 +212,24 @@ impl LayerApp {
         self.width = w as u32;
         self.height = h as u32;
 
+        // The buffer is in device pixels, the surface size in logical ones
+        let (buffer_w, buffer_h) = (self.width as i32 * scale, self.height as i32 * scale);
+
         // Create pool if needed
         if self.pool.is_none() {
             self.pool = Some(
-                SlotPool::new(self.width as usize * self.height as usize * 4, &self.shm_state)
+                SlotPool::new(buffer_w as usize * buffer_h as usize * 4, &self.shm_state)
                     .expect("Failed to create pool"),
             );
         }
 
-        let stride = self.width as i32 * 4;
+        let stride = buffer_w * 4;
 
         // Get buffer from pool
         let (buffer, canvas) = {
             let pool = self.pool.as_mut().unwrap();
-            pool.create_buffer(
-                self.width as i32,
-                self.height as i32,
-                stride,
-                wl_shm::Format::Argb8888,
-            )
-            .expect("create buffer")
+            pool.create_buffer(buffer_w, buffer_h, stride, wl_shm::Format::Argb8888)
+                .expect("create buffer")
         };
 
         // Draw to canvas using unsafe
@@ -185,22 +241,28 @@ impl LayerApp {
             let surface = cairo::ImageSurface::create_for_data(
                 canvas_slice,
                 cairo::Format::ARgb32,
-                self.width as i32,
-                self.height as i32,
+                buffer_w,
+                buffer_h,
                 stride,
             )
             .expect("cairo surface");
+            surface.set_device_scale(scale as f64, scale as f64);
 
             let cr = cairo::Context::new(&surface).expect("cairo context");
-            draw::draw_with_signal(&cr, text, config, signal, draw_state);
+            draw::draw_with_signal(&cr, text, config, signal, percent, draw_state);
             surface.flush();
         }
 
+        // Only the background takes clicks; shadow and animation margin are click-through
+        let (w, h) = (self.width as i32, self.height as i32);
+        self.set_input_region(Some(draw::background_rect(config, signal, draw_state, w, h)));
+
         // Attach buffer to surface
         let layer = self.layer_surface.as_ref().unwrap();
         layer.set_size(self.width, self.height);
+        layer.wl_surface().set_buffer_scale(scale);
         layer.wl_surface().attach(Some(buffer.wl_buffer()), 0, 0);
-        layer.wl_surface().damage(0, 0, self.width as i32, self.height as i32);
+        layer.wl_surface().damage_buffer(0, 0, buffer_w, buffer_h);
         layer.commit();
     }
 
@@ -209,6 +271,7 @@ impl LayerApp {
             self.width = 1;
             self.height = 1;
             layer.set_size(1, 1);
+            self.set_input_region(None);
 
             if let Some(pool) = &mut self.pool {
                 if let Ok((buffer, canvas)) = pool.create_buffer(1, 1, 4, wl_shm::Format::Argb8888)
@@ -216,6 +279,7 @@ impl LayerApp {
                     for i in canvas.iter_mut() {
                         *i = 0;
                     }
+                    layer.wl_surface().set_buffer_scale(1);
                     layer.wl_surface().attach(Some(buffer.wl_buffer()), 0, 0);
                     layer.wl_surface().damage(0, 0, 1, 1);
                     layer.commit();
@@ -227,6 +291,7 @@ impl LayerApp {
 
 delegate_registry!(LayerApp);
 delegate_seat!(LayerApp);
+delegate_pointer!(LayerApp);
 delegate_output!(LayerApp);
 delegate_compositor!(LayerApp);
 delegate_shm!(LayerApp);
@@ -238,8 +303,10 @@ impl CompositorHandler for LayerApp {
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
         _surface: &wl_surface::WlSurface,
-        _new_factor: i32,
+        new_factor: i32,
     ) {
+        // Used from the next frame on
+        self.scale = new_factor.max(1);
     }
 
     fn transform_changed(
@@ -319,10 +386,16 @@ impl SeatHandler for LayerApp {
     fn new_capability(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _seat: wl_seat::WlSeat,
-        _capability: Capability,
+        qh: &QueueHandle<Self>,
+        seat: wl_seat::WlSeat,
+        capability: Capability,
     ) {
+        if capability == Capability::Pointer && self.pointer.is_none() {
+            match self.seat_state.get_pointer(qh, &seat) {
+                Ok(pointer) => self.pointer = Some(pointer),
+                Err(e) => eprintln!("Failed to get pointer: {}", e),
+            }
+        }
     }
 
     fn remove_capability(
@@ -330,14 +403,41 @@ impl SeatHandler for LayerApp {
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
         _seat: wl_seat::WlSeat,
-        _capability: Capability,
+        capability: Capability,
     ) {
+        if capability == Capability::Pointer
+            && let Some(pointer) = self.pointer.take()
+        {
+            pointer.release();
+        }
     }
 
     fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _seat: wl_seat::WlSeat) {
     }
 }
 
+impl PointerHandler for LayerApp {
+    fn pointer_frame(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _pointer: &wl_pointer::WlPointer,
+        events: &[PointerEvent],
+    ) {
+        for event in events {
+            let action = match event.kind {
+                PointerEventKind::Enter { .. } => PointerAction::Enter,
+                PointerEventKind::Leave { .. } => PointerAction::Leave,
+                PointerEventKind::Press { button: BTN_LEFT, .. } => PointerAction::LeftClick,
+                PointerEventKind::Press { button: BTN_MIDDLE, .. } => PointerAction::MiddleClick,
+                PointerEventKind::Press { button: BTN_RIGHT, .. } => PointerAction::RightClick,
+                _ => continue,
+            };
+            self.pointer_actions.push(action);
+        }
+    }
+}
+
 impl LayerShellHandler for LayerApp {
     fn closed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _layer: &LayerSurface) {
         self.exit = true;
@@ -0,0 +1,9 @@
+//! Rendering core of inno, shared by the daemon binary and the
+//! golden-image tests in `tests/`.
+
+pub mod animation;
+pub mod color;
+pub mod config;
+pub mod draw;
+pub mod icons;
+pub mod notification;
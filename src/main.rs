@@ -4,31 +4,38 @@ use smithay_client_toolkit::reexports::client::Connection;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tokio::io::unix::AsyncFd;
 use std::collections::HashMap;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 
-mod config;
+mod client;
 mod control;
+mod daemon;
 mod dbus;
-mod draw;
 mod events;
+mod ipc;
 mod layer;
+mod signals;
+
+use inno::{config, draw, icons};
 
 use config::{AppConfig, HIDE_TIMEOUT_SECS};
 use control::ControlEvent;
 use dbus::Event;
-use draw::{DrawState, format_text};
-use layer::LayerApp;
+use draw::{DrawState, Phase, format_text};
+use inno::notification::{HideReason, Priority, ShowRequest};
+use layer::{LayerApp, PointerAction};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// How long a notification stays after the pointer leaves, if it was due to hide while hovered
+const HOVER_GRACE: Duration = Duration::from_secs(1);
 const HELP: &str = r#"inno - Wayland notification daemon with configurable DBus events
 
 USAGE:
     inno [OPTIONS]
+    inno render [RENDER OPTIONS]
+    inno <COMMAND> [ARGS]
 
 OPTIONS:
     -h, --help              Show this help message
@@ -36,10 +43,39 @@ OPTIONS:
     -d, --debug             Run in debug mode (spitting logs to terminal)
     --daemon                Run in background (daemon mode)
     -l, --log-file <PATH>   Log output to file (useful with --daemon)
-    --no-dbus               Disable DBus control interface
-    --test <number>         Preview specific animation (1-6)
+    --no-dbus               Disable DBus control interface (the control socket still works)
+    --replace               Take over from a running inno, which then exits
+    --test <number>         Preview specific animation (1-11)
     --test-animations       Cycle through all animations for testing
 
+RENDER OPTIONS:
+    -s, --signal <MESSAGE>  Signal to render, by its configured message
+    -p, --percent <NUMBER>  Percentage to format and match signals against
+    --state <STATE>         State to match signals against (default: unknown)
+    -m, --message <TEXT>    Text substituted for {message}
+    -f, --frame <NUMBER>    Animation frame to render (default: 0)
+    -o, --output <PATH>     Output PNG path (default: inno.png)
+    -c, --config <PATH>     Use this config file instead of the search paths
+
+COMMANDS (talk to the running daemon; exit code 3 if there is none):
+    show [TEXT] [--duration <SECS>] [--icon <ICON>] [--signal <MESSAGE>]
+         [--color <COLOR>] [--animation <NAME>] [--percent <NUMBER>]
+         [--body <TEXT>] [--sound <PATH>] [--priority <low|normal|critical>]
+         [--position <POSITION>]
+                            Show a notification and print its id
+    hide                    Hide the current notification
+    reload                  Reload config and event definitions
+    state                   Print battery and device state
+    dnd [on|off|toggle]     Show or set do not disturb
+    history                 List recent notifications
+
+DAEMON COMMANDS:
+    start [OPTIONS]         Start the daemon in the background and wait for it
+    stop                    Stop the daemon, playing the exit animation
+    restart [OPTIONS]       Stop, then start with OPTIONS
+    status                  Print PID, uptime, config, bus health and events
+                            (exit code 3 if not running)
+
 CONFIG:
     ~/.config/inno/inno.toml   (main config)
     ~/.config/inno/events/     (event definitions)
@@ -72,15 +108,137 @@ fn play_sound(path: &PathBuf) {
     });
 }
 
+/// Run a click action's shell command with the notification text in `$INNO_TEXT`
+fn run_click_command(command: &str, text: Option<&str>) {
+    let result = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("INNO_TEXT", text.unwrap_or(""))
+        .spawn();
+    if let Err(e) = result {
+        eprintln!("Failed to run click command '{}': {}", command, e);
+    }
+}
+
+/// Reload the config, keeping the current one if the file has an error
+fn reload_config(config: &mut AppConfig) {
+    match AppConfig::load() {
+        Ok(loaded) => {
+            *config = loaded;
+            eprintln!("inno: reloaded {} signals", config.signals.len());
+            // The icon theme or an icon file may have changed too
+            icons::clear_cache();
+        }
+        Err(e) => eprintln!("inno: keeping the current config: {}", e),
+    }
+}
+
+/// Why a control notification of `priority` is dropped, if it is; `showing`
+/// is the priority of the one on screen
+fn drop_reason(priority: Priority, showing: Option<Priority>, dnd: bool) -> Option<&'static str> {
+    if showing.is_some_and(|current| priority < current) {
+        Some("a higher priority notification is showing")
+    } else if dnd && priority < Priority::Critical {
+        Some("do not disturb is on")
+    } else {
+        None
+    }
+}
+
+/// Render a single notification frame to a PNG without a Wayland connection
+fn run_render(args: &[String]) -> anyhow::Result<()> {
+    let mut signal_name: Option<String> = None;
+    let mut percent: Option<f64> = None;
+    let mut state = "unknown".to_string();
+    let mut message = String::new();
+    let mut frame: u32 = 0;
+    let mut output = PathBuf::from("inno.png");
+    let mut config_path: Option<PathBuf> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if flag == "-h" || flag == "--help" {
+            // The render section of the main help
+            let start = HELP.find("RENDER OPTIONS:").unwrap_or(0);
+            let end = HELP[start..].find("\n\n").map_or(HELP.len(), |n| start + n + 1);
+            print!("USAGE:\n    inno render [RENDER OPTIONS]\n\n{}", &HELP[start..end]);
+            return Ok(());
+        }
+        let Some(value) = args.get(i + 1) else {
+            anyhow::bail!("Missing value for {}", flag);
+        };
+        match flag {
+            "-s" | "--signal" => signal_name = Some(value.clone()),
+            "-p" | "--percent" => percent = Some(value.parse()?),
+            "--state" => state = value.to_lowercase(),
+            "-m" | "--message" => message = value.clone(),
+            "-f" | "--frame" => frame = value.parse()?,
+            "-o" | "--output" => output = PathBuf::from(value),
+            "-c" | "--config" => config_path = Some(PathBuf::from(value)),
+            _ => anyhow::bail!("Unknown render option: {}", flag),
+        }
+        i += 2;
+    }
+
+    let config = match config_path {
+        Some(ref path) => AppConfig::load_from(path)?,
+        None => AppConfig::load()?,
+    };
+
+    let signal = match signal_name {
+        Some(ref name) => match config.find_signal_by_message(name) {
+            Some(sig) => Some(sig),
+            None => anyhow::bail!("No signal with message '{}' in config", name),
+        },
+        None => {
+            config.find_signal_idx(percent.unwrap_or(100.0), &state).map(|idx| &config.signals[idx])
+        }
+    };
+
+    let text = match signal {
+        Some(sig) => {
+            let dynamic_msg = sig.message.replace("{message}", &message);
+            format_text(&config.format, sig.icon.glyph(), &dynamic_msg, percent)
+        }
+        None => format_text(&config.format, "", &message, percent),
+    };
+
+    let mut draw_state = DrawState::default();
+    if let Some(sig) = signal {
+        draw_state.start(sig);
+        for _ in 0..frame {
+            draw_state.tick_signal(sig, config.fps as f64);
+        }
+    }
+
+    let (w, h) = draw::render_png(&output, &text, &config, signal, percent, &draw_state)?;
+    println!("Rendered {}x{} notification to {}", w, h, output.display());
+
+    Ok(())
+}
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
     let args: Vec<String> = std::env::args().collect();
+
+    if args.get(1).map(String::as_str) == Some("render") {
+        return run_render(&args[2..]);
+    }
+    if let Some(command) = args.get(1).filter(|a| client::COMMANDS.contains(&a.as_str())) {
+        std::process::exit(client::run(command, &args[2..]).await);
+    }
+    if let Some(command) = args.get(1).filter(|a| daemon::COMMANDS.contains(&a.as_str())) {
+        std::process::exit(daemon::run(command, &args[2..]).await);
+    }
+
     let mut log_file: Option<PathBuf> = None;
-    let enable_dbus = true;
+    let mut enable_dbus = true;
     let mut debug_mode = false;
     let mut is_internal_daemon = false;
     let mut test_animations = false;
     let mut specific_test_anim: Option<usize> = None;
+    let mut replace = false;
 
     // First pass to check critical flags
     for arg in &args {
@@ -120,11 +278,17 @@ async fn main() -> anyhow::Result<()> {
             "--test-animations" => {
                 test_animations = true;
             }
+            "--replace" => {
+                replace = true;
+            }
+            "--no-dbus" => {
+                enable_dbus = false;
+            }
             "--test" => {
                 i += 1;
                 if i < args.len() {
                     if let Ok(idx) = args[i].parse::<usize>() {
-                        if (1..=6).contains(&idx) {
+                        if (1..=11).contains(&idx) {
                             specific_test_anim = Some(idx - 1);
                             test_animations = true;
                             debug_mode = true;
@@ -137,11 +301,18 @@ async fn main() -> anyhow::Result<()> {
         i += 1;
     }
 
+    // Refuse to start a second daemon before detaching, so the message is seen
+    let running = ipc::is_running() || (enable_dbus && client::connect().await.is_some());
+    if running && !replace && !test_animations {
+        eprintln!("inno: {}", control::AlreadyRunning);
+        std::process::exit(1);
+    }
+
     if debug_mode {
         println!("inno is running in debug mode.");
     } else if !is_internal_daemon {
         // Parent process: Handle daemonization via self-respawn
-        println!("inno is running as a daemon. To stop it, use 'pkill inno'.");
+        println!("inno is running as a daemon. To stop it, use 'inno stop'.");
         use std::os::unix::process::CommandExt;
 
         let mut cmd = std::process::Command::new(&args[0]);
@@ -180,41 +351,73 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    let mut config = AppConfig::load();
+    // A broken config stops the daemon here rather than running with defaults
+    let mut config = AppConfig::load()?;
     eprintln!("inno: loaded {} signals", config.signals.len());
 
     // Load event configurations
-    let event_configs = events::load_events();
+    let mut event_configs = events::load_events();
     eprintln!("inno: loaded {} event configs", event_configs.len());
+    // EnableEvent/DisableEvent by name, kept across reloads
+    let mut event_overrides: HashMap<String, bool> = HashMap::new();
 
     // Channels
     let (tx, mut rx) = mpsc::channel(10);
+    let (events_tx, events_rx) = watch::channel(event_configs.clone());
+    // Events from TriggerEvent go through the same path as real ones
+    let trigger_tx = tx.clone();
     let (config_tx, mut config_rx) = mpsc::channel::<()>(1);
     let (control_tx, mut control_rx) = mpsc::channel::<ControlEvent>(10);
 
-    // Shared battery state for DBus interface
-    let battery_percentage = Arc::new(AtomicU32::new(10000)); // 100.00%
-    let battery_state_shared = Arc::new(RwLock::new("unknown".to_string()));
+    // What is shown, the devices and so on, for the control interfaces
+    let status = control::SharedStatus::default();
+    status.lock().unwrap().started = control::unix_time();
+    status.lock().unwrap().config_path = config.config_path.clone();
 
     // Start DBus control interface
-    let _dbus_conn = if enable_dbus {
-        match control::start_control_service(
-            control_tx.clone(),
-            battery_percentage.clone(),
-            battery_state_shared.clone(),
-        )
-        .await
-        {
-            Ok(conn) => Some(conn),
+    let dbus_conn = if enable_dbus {
+        match control::start_control_service(control_tx.clone(), status.clone(), replace).await {
+            Ok(conn) => {
+                let health = format!("registered as {}", control::BUS_NAME);
+                status.lock().unwrap().buses.insert("control".to_string(), health);
+                Some(conn)
+            }
+            Err(e) if e.is::<control::AlreadyRunning>() && !test_animations => {
+                eprintln!("inno: {}", e);
+                std::process::exit(1);
+            }
             Err(e) => {
                 eprintln!("Failed to start DBus control interface: {}", e);
+                let health = format!("error: {}", e);
+                status.lock().unwrap().buses.insert("control".to_string(), health);
                 None
             }
         }
     } else {
+        status.lock().unwrap().buses.insert("control".to_string(), "disabled".to_string());
         None
     };
 
+    let announcer = control::Announcer::new(status.clone(), dbus_conn.as_ref()).await;
+
+    // Start the control socket, which works without DBus
+    let _socket = match ipc::start(control_tx.clone(), announcer.clone(), replace) {
+        Ok(guard) => Some(guard),
+        Err(e) if e.is::<control::AlreadyRunning>() && !test_animations => {
+            eprintln!("inno: {}", e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to start control socket: {}", e);
+            None
+        }
+    };
+
+    // SIGTERM and SIGINT shut down, SIGHUP reloads, SIGUSR1 logs the state
+    if let Err(e) = signals::start(control_tx.clone(), announcer.clone()) {
+        eprintln!("Failed to install signal handlers: {}", e);
+    }
+
     // Watch config file for changes
     if let Some(ref config_path) = config.config_path {
         let config_path = config_path.clone();
@@ -244,7 +447,7 @@ async fn main() -> anyhow::Result<()> {
     // Start DBus event listener with configurable events
     if !test_animations {
         tokio::spawn(async move {
-            if let Err(e) = dbus::run_dbus_listener(tx, event_configs).await {
+            if let Err(e) = dbus::run_dbus_listener(tx, events_rx, status).await {
                 eprintln!("DBus error: {}", e);
             }
         });
@@ -262,11 +465,19 @@ async fn main() -> anyhow::Result<()> {
     app.create_surface(&qh, &config);
     event_queue.blocking_dispatch(&mut app)?;
 
+    // Written once up, so `inno start` knows the daemon made it this far
+    let _pid_file = if test_animations {
+        None
+    } else {
+        daemon::PidFile::create().map_err(|e| eprintln!("Failed to write PID file: {}", e)).ok()
+    };
+
     let backend = conn.backend();
     let fd = backend.poll_fd();
     let async_fd = AsyncFd::new(fd)?;
 
     let mut current_text: Option<String> = None;
+    let mut current_percent: Option<f64> = None;
     let mut prev_state: HashMap<String, Option<String>> = HashMap::new();
     let mut prev_signal_msg: HashMap<String, Option<String>> = HashMap::new();
     let mut draw_state = DrawState::default();
@@ -274,8 +485,15 @@ async fn main() -> anyhow::Result<()> {
     let mut animation_timer =
         Box::pin(tokio::time::sleep(Duration::from_micros(1_000_000 / config.fps)));
     let mut animating = false;
-    // Cache current signal index to avoid re-searching every animation frame
-    let mut current_signal_idx: Option<usize> = None;
+    // The signal being shown, kept for animation frames and the exit animation
+    let mut current_signal: Option<config::Signal> = None;
+    let mut current_priority = Priority::Normal;
+    let mut next_notification_id: u32 = 0;
+    // Id of the notification on screen and why it is about to hide
+    let mut current_id: Option<u32> = None;
+    let mut hide_reason: Option<HideReason> = None;
+    // The control request being shown, for Update
+    let mut current_request: Option<ShowRequest> = None;
     let test_animations_list = vec![
         config::Animation::Blink,
         config::Animation::Pulse,
@@ -283,10 +501,19 @@ async fn main() -> anyhow::Result<()> {
         config::Animation::SlideRight,
         config::Animation::SlideLeft,
         config::Animation::Bounce,
+        config::Animation::SlideUp,
+        config::Animation::SlideDown,
+        config::Animation::Zoom,
+        config::Animation::Shake,
+        config::Animation::Typewriter,
     ];
-    let mut current_test_signal: Option<config::Signal> = None;
+    // Hiding waits while the pointer is over the notification
+    let mut hovered = false;
+    let mut hide_deferred = false;
     let mut test_anim_idx = specific_test_anim.unwrap_or(0);
     let mut test_timer = Box::pin(tokio::time::sleep(Duration::from_secs(0)));
+    // Set by Shutdown; the loop ends once the exit animation has played
+    let mut shutting_down = false;
 
     if test_animations {
         eprintln!("Animation testing mode enabled.");
@@ -300,14 +527,61 @@ async fn main() -> anyhow::Result<()> {
             break;
         }
 
+        for action in std::mem::take(&mut app.pointer_actions) {
+            let click = match action {
+                PointerAction::Enter => {
+                    hovered = true;
+                    continue;
+                }
+                PointerAction::Leave => {
+                    hovered = false;
+                    if std::mem::take(&mut hide_deferred) {
+                        hide_timer = Box::pin(tokio::time::sleep(HOVER_GRACE));
+                    }
+                    continue;
+                }
+                PointerAction::LeftClick => &config.pointer.left_click,
+                PointerAction::MiddleClick => &config.pointer.middle_click,
+                PointerAction::RightClick => &config.pointer.right_click,
+            };
+            if current_text.is_none() {
+                continue;
+            }
+            match click {
+                config::ClickAction::None => {}
+                config::ClickAction::Dismiss => {
+                    // Hide through the hide timer so the exit animation plays
+                    hovered = false;
+                    hide_deferred = false;
+                    hide_reason = Some(HideReason::Dismissed);
+                    hide_timer = Box::pin(tokio::time::sleep(Duration::ZERO));
+                }
+                config::ClickAction::DismissAll => {
+                    app.hide();
+                    if let Some(id) = current_id.take() {
+                        announcer.hidden(id, HideReason::Dismissed).await;
+                    }
+                    current_text = None;
+                    current_request = None;
+                    animating = false;
+                    hide_deferred = false;
+                    draw_state.reset();
+                    hide_timer =
+                        Box::pin(tokio::time::sleep(Duration::from_secs(HIDE_TIMEOUT_SECS)));
+                }
+                config::ClickAction::Command(command) => {
+                    run_click_command(command, current_text.as_deref())
+                }
+            }
+        }
+
         let _ = conn.flush();
 
         tokio::select! {
             // Config reload (from file watcher)
             Some(()) = config_rx.recv() => {
                 eprintln!("Config file changed, reloading...");
-                config = AppConfig::load();
-                eprintln!("inno: reloaded {} signals", config.signals.len());
+                reload_config(&mut config);
                 // Update animation interval if FPS changed
                 animation_timer = Box::pin(tokio::time::sleep(Duration::from_micros(1_000_000 / config.fps)));
             }
@@ -315,46 +589,216 @@ async fn main() -> anyhow::Result<()> {
             // DBus control events
             Some(control_event) = control_rx.recv() => {
                 match control_event {
-                    ControlEvent::Show { message, duration } => {
-                        eprintln!("DBus: Show '{}' for {}s", message, duration);
+                    ControlEvent::Show { message, duration, reply } => {
+                        // Like ShowEx, a dropped request gets no id
+                        let showing = current_text.as_ref().map(|_| current_priority);
+                        if let Some(why) = drop_reason(Priority::Normal, showing, announcer.do_not_disturb()) {
+                            eprintln!("DBus: Show dropped, {}", why);
+                            let _ = reply.send(Err(format!("dropped, {}", why)));
+                            continue;
+                        }
+                        next_notification_id += 1;
+                        let id = next_notification_id;
+                        let _ = reply.send(Ok(id));
+                        eprintln!("DBus: Show {} '{}' for {}s", id, message, duration);
                         draw_state.reset();
                         app.draw_text(&message, &config);
                         hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(duration)));
+                        if let Some(old) = current_id.replace(id) {
+                            announcer.hidden(old, HideReason::Replaced).await;
+                        }
+                        announcer.shown(id, "control", &message).await;
+                        hide_reason = None;
+                        current_request = Some(ShowRequest {
+                            message: Some(message.clone()),
+                            duration: Some(duration),
+                            ..ShowRequest::default()
+                        });
                         current_text = Some(message);
+                        current_percent = None;
+                        current_signal = None;
+                        current_priority = Priority::Normal;
                         animating = false;
                     }
+                    ControlEvent::ShowEx { request, reply } => {
+                        let sig = match request.to_signal(&config) {
+                            Ok(sig) => sig,
+                            Err(e) => {
+                                let _ = reply.send(Err(e));
+                                continue;
+                            }
+                        };
+                        // A dropped request gets no id, so callers can't wait on it
+                        let priority = request.priority.unwrap_or_default();
+                        let showing = current_text.as_ref().map(|_| current_priority);
+                        if let Some(why) = drop_reason(priority, showing, announcer.do_not_disturb()) {
+                            eprintln!("DBus: ShowEx dropped, {}", why);
+                            let _ = reply.send(Err(format!("dropped, {}", why)));
+                            continue;
+                        }
+                        next_notification_id += 1;
+                        let id = next_notification_id;
+                        let _ = reply.send(Ok(id));
+
+                        let text = request.text(&config, &sig);
+                        eprintln!("DBus: ShowEx {} '{}'", id, text);
+                        if let Some(ref sound_path) = sig.sound {
+                            play_sound(sound_path);
+                        }
+
+                        draw_state.start(&sig);
+                        app.draw_text_with_signal(&text, &config, Some(&sig), request.percent, &draw_state);
+                        animating = sig.is_animated();
+                        hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(sig.duration)));
+                        if let Some(old) = current_id.replace(id) {
+                            announcer.hidden(old, HideReason::Replaced).await;
+                        }
+                        announcer.shown(id, "control", &text).await;
+                        hide_reason = None;
+                        current_text = Some(text);
+                        current_percent = request.percent;
+                        current_signal = Some(sig);
+                        current_priority = priority;
+                        current_request = Some(*request);
+                    }
+                    ControlEvent::Update { id, request: update, reply } => {
+                        // Once the exit animation has started it is as good as gone
+                        let leaving = matches!(draw_state.phase, Phase::Exit | Phase::Done);
+                        let Some(request) = current_request.as_mut().filter(|_| current_id == Some(id) && !leaving) else {
+                            let _ = reply.send(Err(format!("notification {} is not showing", id)));
+                            continue;
+                        };
+                        let mut updated = request.clone();
+                        updated.merge(*update);
+                        let sig = match updated.to_signal(&config) {
+                            Ok(sig) => sig,
+                            Err(e) => {
+                                let _ = reply.send(Err(e));
+                                continue;
+                            }
+                        };
+                        let _ = reply.send(Ok(()));
+
+                        // Redraw in place, the animation carries on where it is
+                        let text = updated.text(&config, &sig);
+                        eprintln!("DBus: Update {} '{}'", id, text);
+                        app.draw_text_with_signal(&text, &config, Some(&sig), updated.percent, &draw_state);
+                        announcer.updated(id, &text).await;
+                        animating = animating || sig.is_animated();
+                        hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(sig.duration)));
+                        current_text = Some(text);
+                        current_percent = updated.percent;
+                        current_signal = Some(sig);
+                        current_priority = updated.priority.unwrap_or_default();
+                        *request = updated;
+                    }
+                    ControlEvent::Close { id } => {
+                        if current_id == Some(id) {
+                            eprintln!("DBus: Close {}", id);
+                            // Through the hide timer so the exit animation plays
+                            hovered = false;
+                            hide_deferred = false;
+                            hide_reason = Some(HideReason::Closed);
+                            hide_timer = Box::pin(tokio::time::sleep(Duration::ZERO));
+                        }
+                    }
                     ControlEvent::Hide => {
                         eprintln!("DBus: Hide");
                         app.hide();
+                        if let Some(id) = current_id.take() {
+                            announcer.hidden(id, HideReason::Closed).await;
+                        }
                         current_text = None;
+                        current_request = None;
                         animating = false;
                         hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(HIDE_TIMEOUT_SECS)));
                     }
+                    ControlEvent::ListEvents { reply } => {
+                        let list = event_configs
+                            .iter()
+                            .map(|e| (e.name.clone(), e.enabled, e.bus.clone()))
+                            .collect();
+                        let _ = reply.send(list);
+                    }
+                    ControlEvent::SetEventEnabled { name, enabled, reply } => {
+                        match event_configs.iter_mut().find(|e| e.name == name) {
+                            Some(event) => {
+                                eprintln!("DBus: {} event '{}'", if enabled { "Enable" } else { "Disable" }, name);
+                                event.enabled = enabled;
+                                event_overrides.insert(name, enabled);
+                                let _ = events_tx.send(event_configs.clone());
+                                let _ = reply.send(Ok(()));
+                            }
+                            None => {
+                                let _ = reply.send(Err(format!("no event '{}'", name)));
+                            }
+                        }
+                    }
+                    ControlEvent::TriggerEvent { name, values, reply } => {
+                        match event_configs.iter().find(|e| e.name == name) {
+                            Some(event) => {
+                                eprintln!("DBus: Trigger event '{}'", name);
+                                // Not awaited: this loop is the one draining the channel
+                                let sent = trigger_tx
+                                    .try_send(Event::Notify(dbus::trigger_event(event, values)))
+                                    .map_err(|e| e.to_string());
+                                let _ = reply.send(sent);
+                            }
+                            None => {
+                                let _ = reply.send(Err(format!("no event '{}'", name)));
+                            }
+                        }
+                    }
+                    ControlEvent::Shutdown => {
+                        eprintln!("Shutting down");
+                        if current_text.is_none() {
+                            break;
+                        }
+                        // Through the hide timer so the exit animation plays
+                        shutting_down = true;
+                        hovered = false;
+                        hide_deferred = false;
+                        hide_reason = Some(HideReason::Closed);
+                        hide_timer = Box::pin(tokio::time::sleep(Duration::ZERO));
+                    }
                     ControlEvent::Reload => {
-                        eprintln!("DBus: Reload config");
-                        config = AppConfig::load();
-                        eprintln!("inno: reloaded {} signals", config.signals.len());
+                        eprintln!("Reloading config");
+                        reload_config(&mut config);
+                        announcer.status().lock().unwrap().config_path = config.config_path.clone();
+                        event_configs = events::load_events();
+                        for event in &mut event_configs {
+                            if let Some(&enabled) = event_overrides.get(&event.name) {
+                                event.enabled = enabled;
+                            }
+                        }
+                        eprintln!("inno: reloaded {} event configs", event_configs.len());
+                        let _ = events_tx.send(event_configs.clone());
                     }
                 }
             }
 
-            Some(event) = rx.recv() => {
+            // Nothing new is shown while shutting down
+            Some(event) = rx.recv(), if !shutting_down => {
                 match event {
                     Event::Notify(notify_event) => {
-                        // Update shared state for DBus control interface
-                        if let Some(pct) = notify_event.percentage {
-                            battery_percentage.store((pct * 100.0) as u32, Ordering::Relaxed);
-                        }
-                        if let Some(ref state) = notify_event.state {
-                            if let Ok(mut s) = battery_state_shared.write() {
-                                *s = state.clone();
-                            }
+                        // Per-device state for the DBus control interface
+                        let reading = notify_event.percentage.is_some() || notify_event.state.is_some();
+                        if reading && !notify_event.triggered {
+                            announcer
+                                .device(
+                                    &notify_event.path,
+                                    &notify_event.event_name,
+                                    notify_event.percentage,
+                                    notify_event.state.as_deref(),
+                                    notify_event.values.get("name").map(String::as_str),
+                                    notify_event.power_supply,
+                                )
+                                .await;
                         }
 
                         let pct_for_match = notify_event.percentage.unwrap_or(100.0);
                         let state = notify_event.state.clone().unwrap_or_else(|| "unknown".to_string());
 
-                        // Find matching signal and cache its index for animation frames
                         let sig_idx = config.find_signal_idx(pct_for_match, &state);
                         let signal = sig_idx.map(|i| &config.signals[i]);
                         let signal_msg = signal.map(|s| s.message.clone());
@@ -366,7 +810,7 @@ async fn main() -> anyhow::Result<()> {
                         let prev_sig = prev_signal_msg.get(&state_key).unwrap_or(&None);
                         let signal_changed = prev_sig != &signal_msg;
 
-                        if state_changed || signal_changed {
+                        if state_changed || signal_changed || notify_event.triggered {
                             if let Some(p) = notify_event.percentage {
                                 println!("Notify: {:.0}% {} ({}) (state={}, signal={})",
                                     p, notify_event.event_name, notify_event.path, state_changed, signal_changed);
@@ -375,12 +819,18 @@ async fn main() -> anyhow::Result<()> {
                                     notify_event.event_name, notify_event.path, state_changed, signal_changed);
                             }
 
-                            if let Some(sig) = signal {
-                                let dynamic_msg = sig.message.replace("{message}", &notify_event.message);
+                            if let Some(sig) = signal.filter(|_| !announcer.do_not_disturb()) {
+                                // Event values come from DBus and must not be read as markup
+                                let event_msg = if config.markup {
+                                    draw::escape_markup(&notify_event.message)
+                                } else {
+                                    notify_event.message.clone()
+                                };
+                                let dynamic_msg = sig.message.replace("{message}", &event_msg);
 
                                 let text = format_text(
                                     &config.format,
-                                    &sig.icon,
+                                    sig.icon.glyph(),
                                     &dynamic_msg,
                                     notify_event.percentage,
                                 );
@@ -389,12 +839,21 @@ async fn main() -> anyhow::Result<()> {
                                     play_sound(sound_path);
                                 }
 
-                                draw_state.reset();
-                                app.draw_text_with_signal(&text, &config, Some(sig), &draw_state);
-                                animating = sig.animation != config::Animation::None;
-                                current_signal_idx = sig_idx;
+                                draw_state.start(sig);
+                                app.draw_text_with_signal(&text, &config, Some(sig), notify_event.percentage, &draw_state);
+                                next_notification_id += 1;
+                                if let Some(old) = current_id.replace(next_notification_id) {
+                                    announcer.hidden(old, HideReason::Replaced).await;
+                                }
+                                announcer.shown(next_notification_id, &notify_event.event_name, &text).await;
+                                hide_reason = None;
+                                animating = sig.is_animated();
+                                current_signal = Some(sig.clone());
+                                current_priority = Priority::Normal;
+                                current_request = None;
                                 hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(sig.duration)));
                                 current_text = Some(text);
+                                current_percent = notify_event.percentage;
                             }
                         }
 
@@ -411,27 +870,38 @@ async fn main() -> anyhow::Result<()> {
 
                 let test_signal = config::Signal {
                     message: format!("Testing {}", anim_name),
-                    icon: "󰚗".to_string(),
+                    icon: config::Icon::Glyph("󰚗".to_string()),
                     icon_size: 24.0,
+                    icon_recolor: None,
                     color: (0.2, 0.8, 0.2, 1.0),
                     threshold: 0.0,
                     state_filter: "any".to_string(),
                     animation: anim,
                     duration: 10,
                     sound: None,
+                    bar: false,
+                    ..Default::default()
                 };
 
                 let text = format_text(
                     &config.format,
-                    &test_signal.icon,
+                    test_signal.icon.glyph(),
                     &test_signal.message,
                     Some(50.0),
                 );
 
                 current_text = Some(text.clone());
-                draw_state.reset();
-                app.draw_text_with_signal(&text, &config, Some(&test_signal), &draw_state);
-                current_test_signal = Some(test_signal);
+                current_percent = Some(50.0);
+                draw_state.start(&test_signal);
+                app.draw_text_with_signal(&text, &config, Some(&test_signal), current_percent, &draw_state);
+                next_notification_id += 1;
+                if let Some(old) = current_id.replace(next_notification_id) {
+                    announcer.hidden(old, HideReason::Replaced).await;
+                }
+                announcer.shown(next_notification_id, "test", &text).await;
+                hide_reason = None;
+                current_signal = Some(test_signal);
+                current_request = None;
                 hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(10)));
 
                 if let Some(fixed_idx) = specific_test_anim {
@@ -446,29 +916,42 @@ async fn main() -> anyhow::Result<()> {
 
             _ = &mut animation_timer, if animating => {
                 if let Some(text) = &current_text {
-                    if test_animations {
-                        // Reuse the cached test signal instead of recreating it every frame
-                        if let Some(ref sig) = current_test_signal {
-                            let total_frames = sig.duration as f64 * config.fps as f64;
-                            draw_state.tick(&sig.animation, total_frames, config.fps as f64);
-                            app.draw_text_with_signal(text, &config, Some(sig), &draw_state);
-                        }
-                    } else if let Some(idx) = current_signal_idx {
-                        // Use cached signal index instead of re-searching every frame
-                        let signal = &config.signals[idx];
-                        let total_frames = signal.duration as f64 * config.fps as f64;
-                        draw_state.tick(&signal.animation, total_frames, config.fps as f64);
-                        app.draw_text_with_signal(text, &config, Some(signal), &draw_state);
+                    if let Some(ref sig) = current_signal {
+                        draw_state.tick_signal(sig, config.fps as f64);
+                        app.draw_text_with_signal(text, &config, Some(sig), current_percent, &draw_state);
+                    }
+                    if draw_state.phase == Phase::Done {
+                        // Exit animation finished, hide right away
+                        animating = false;
+                        hide_timer = Box::pin(tokio::time::sleep(Duration::ZERO));
                     }
                 }
                 animation_timer = Box::pin(tokio::time::sleep(Duration::from_micros(1_000_000 / config.fps)));
             }
 
             _ = &mut hide_timer => {
-                if current_text.is_some() {
+                let signal = current_signal.as_ref();
+                let showing = current_text.is_some()
+                    && matches!(draw_state.phase, Phase::Enter | Phase::Idle);
+                if showing && hovered && config.pointer.hover_pause && !shutting_down {
+                    // Hidden shortly after the pointer leaves
+                    hide_deferred = true;
+                    hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(HIDE_TIMEOUT_SECS)));
+                    continue;
+                }
+                let exiting = showing && signal.is_some_and(|sig| draw_state.start_exit(sig));
+                if exiting {
+                    // The animation timer hides the surface once the exit animation is done
+                    animating = true;
+                    hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(HIDE_TIMEOUT_SECS)));
+                } else if current_text.is_some() {
                     println!("Auto-hiding");
                     app.hide();
+                    if let Some(id) = current_id.take() {
+                        announcer.hidden(id, hide_reason.take().unwrap_or(HideReason::Expired)).await;
+                    }
                     current_text = None;
+                    current_request = None;
                     animating = false;
                     draw_state.reset();
                     hide_timer = Box::pin(tokio::time::sleep(Duration::from_secs(HIDE_TIMEOUT_SECS)));
@@ -478,6 +961,9 @@ async fn main() -> anyhow::Result<()> {
                         break;
                     }
                 }
+                if shutting_down && current_text.is_none() {
+                    break;
+                }
             }
 
             guard = async_fd.readable() => {
@@ -514,5 +1000,9 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // Let go of the surface before the bus name and socket go with the rest
+    app.hide();
+    let _ = conn.flush();
+
     Ok(())
 }
@@ -0,0 +1,178 @@
+//! Notifications requested by scripts over the control interface
+//!
+//! A request names a configured `[[signal]]` to start from, or none for a
+//! plain notification, and overrides its icon, colour, animation and so on.
+
+use crate::config::{Anchor, AppConfig, DEFAULT_ICON_SIZE, Icon, Signal};
+use crate::draw::format_text;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Whether a notification may replace the one on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize)]
+#[serde(try_from = "PriorityValue")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl Priority {
+    /// `low`, `normal` or `critical`, or the freedesktop urgency levels 0-2
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "low" | "0" => Some(Priority::Low),
+            "normal" | "1" => Some(Priority::Normal),
+            "critical" | "urgent" | "high" | "2" => Some(Priority::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for Priority {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Priority::parse(&s).ok_or_else(|| format!("unknown priority '{}'", s))
+    }
+}
+
+/// A priority as a name or a level, as `ShowEx` takes it
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PriorityValue {
+    Name(String),
+    Level(u64),
+}
+
+impl TryFrom<PriorityValue> for Priority {
+    type Error = String;
+
+    fn try_from(value: PriorityValue) -> Result<Self, Self::Error> {
+        match value {
+            PriorityValue::Name(s) => s.try_into(),
+            PriorityValue::Level(n) => n.to_string().try_into(),
+        }
+    }
+}
+
+/// A notification request; unset fields come from the named signal or the defaults
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShowRequest {
+    pub message: Option<String>,
+    pub body: Option<String>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    pub animation: Option<String>,
+    pub sound: Option<String>,
+    pub priority: Option<Priority>,
+    /// Fills the percentage bar and the `{percent}` placeholder
+    pub percent: Option<f64>,
+    pub position: Option<String>,
+    /// Message of a configured `[[signal]]` to start from
+    pub signal: Option<String>,
+    /// Seconds on screen
+    pub duration: Option<u64>,
+}
+
+impl ShowRequest {
+    /// Build the signal to draw, or an error naming the bad field
+    pub fn to_signal(&self, config: &AppConfig) -> Result<Signal, String> {
+        let mut signal = match &self.signal {
+            Some(name) => config
+                .find_signal_by_message(name)
+                .cloned()
+                .ok_or_else(|| format!("no signal '{}' in config", name))?,
+            None => Signal {
+                message: "{message}".to_string(),
+                icon_size: DEFAULT_ICON_SIZE,
+                color: config.text_color,
+                state_filter: "any".to_string(),
+                duration: 5,
+                // A bar only makes sense with a percentage
+                bar: self.percent.is_some(),
+                ..Signal::default()
+            },
+        };
+
+        if let Some(icon) = &self.icon {
+            signal.icon = Icon::parse(icon);
+            signal.icon_frames.clear();
+        }
+        if let Some(color) = &self.color {
+            signal.color =
+                config.lookup_color(color).ok_or_else(|| format!("unknown colour '{}'", color))?;
+            signal.color_ramp = None;
+        }
+        if let Some(name) = &self.animation {
+            signal.animation = config
+                .lookup_animation(name)
+                .ok_or_else(|| format!("unknown animation '{}'", name))?;
+        }
+        if let Some(sound) = &self.sound {
+            signal.sound = Some(PathBuf::from(sound));
+        }
+        if let Some(position) = &self.position {
+            signal.anchor = Some(Anchor::parse(position));
+        }
+        if let Some(body) = &self.body {
+            signal.body = Some(body.clone());
+        }
+        if let Some(duration) = self.duration {
+            signal.duration = duration;
+        }
+        Ok(signal)
+    }
+
+    /// Apply the fields set in `update`, keeping the rest
+    pub fn merge(&mut self, update: ShowRequest) {
+        fn set<T>(field: &mut Option<T>, value: Option<T>) {
+            if value.is_some() {
+                *field = value;
+            }
+        }
+        set(&mut self.message, update.message);
+        set(&mut self.body, update.body);
+        set(&mut self.icon, update.icon);
+        set(&mut self.color, update.color);
+        set(&mut self.animation, update.animation);
+        set(&mut self.sound, update.sound);
+        set(&mut self.priority, update.priority);
+        set(&mut self.percent, update.percent);
+        set(&mut self.position, update.position);
+        set(&mut self.signal, update.signal);
+        set(&mut self.duration, update.duration);
+    }
+
+    /// The notification text for `signal`, as built by `to_signal`
+    pub fn text(&self, config: &AppConfig, signal: &Signal) -> String {
+        let message = signal.message.replace("{message}", self.message.as_deref().unwrap_or(""));
+        format_text(&config.format, signal.icon.glyph(), &message, self.percent)
+    }
+}
+
+/// Why a notification went away, as reported over the control interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HideReason {
+    /// Its duration ran out
+    Expired,
+    /// Clicked away
+    Dismissed,
+    /// Closed or hidden by a control request
+    Closed,
+    /// Another notification took its place
+    Replaced,
+}
+
+impl HideReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HideReason::Expired => "expired",
+            HideReason::Dismissed => "dismissed",
+            HideReason::Closed => "closed",
+            HideReason::Replaced => "replaced",
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Unix signals for the daemon
+//!
+//! SIGTERM and SIGINT shut down the way `Shutdown` does, SIGHUP reloads like
+//! `Reload`, and SIGUSR1 writes the current state to the log.
+
+use crate::control::{Announcer, ControlEvent};
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::{mpsc, oneshot};
+
+/// Turn signals into control events until the daemon exits
+pub fn start(tx: mpsc::Sender<ControlEvent>, announcer: Announcer) -> std::io::Result<()> {
+    let mut term = signal(SignalKind::terminate())?;
+    let mut int = signal(SignalKind::interrupt())?;
+    let mut hup = signal(SignalKind::hangup())?;
+    let mut usr1 = signal(SignalKind::user_defined1())?;
+
+    tokio::spawn(async move {
+        let mut stopping = false;
+        loop {
+            let event = tokio::select! {
+                _ = term.recv() => "SIGTERM",
+                _ = int.recv() => "SIGINT",
+                _ = hup.recv() => "SIGHUP",
+                _ = usr1.recv() => "SIGUSR1",
+            };
+            eprintln!("inno: received {}", event);
+            match event {
+                "SIGHUP" => {
+                    let _ = tx.send(ControlEvent::Reload).await;
+                }
+                "SIGUSR1" => dump_state(&tx, &announcer).await,
+                // A second one does not wait for the exit animation
+                _ if stopping => std::process::exit(1),
+                _ => {
+                    stopping = true;
+                    let _ = tx.send(ControlEvent::Shutdown).await;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Log what is shown, the devices, do-not-disturb and the events
+async fn dump_state(tx: &mpsc::Sender<ControlEvent>, announcer: &Announcer) {
+    {
+        let status = announcer.status().lock().unwrap();
+        let (id, event, text) = &status.current;
+        if *id == 0 {
+            eprintln!("state: nothing shown");
+        } else {
+            eprintln!("state: showing {} from {}: {}", id, event, text);
+        }
+        eprintln!("state: do not disturb {}", if status.do_not_disturb { "on" } else { "off" });
+        for (path, device) in &status.devices {
+            eprintln!(
+                "state: device {}: {:.0}% {} ({}, {})",
+                device.name, device.percent, device.state, device.event, path
+            );
+        }
+        eprintln!("state: {} notifications in history", status.history.len());
+    }
+
+    let (reply, rx) = oneshot::channel();
+    if tx.send(ControlEvent::ListEvents { reply }).await.is_err() {
+        return;
+    }
+    for (name, enabled, bus) in rx.await.unwrap_or_default() {
+        let enabled = if enabled { "enabled" } else { "disabled" };
+        eprintln!("state: event '{}' on the {} bus, {}", name, bus, enabled);
+    }
+}
@@ -0,0 +1,197 @@
+//! Keyframe animations: easing curves, property interpolation, the
+//! `[animations.<name>]` config tables and enter/idle/exit phases.
+
+use inno::animation::{Easing, Keyframe, KeyframeAnimation};
+use inno::config::{Animation, AppConfig, ConfigError};
+use inno::draw::{DrawState, Phase};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-6
+}
+
+#[test]
+fn easing_names() {
+    assert_eq!(Easing::parse("linear"), Some(Easing::Linear));
+    assert_eq!(Easing::parse("ease-in-cubic"), Some(Easing::EaseIn));
+    assert_eq!(Easing::parse("ease_out"), Some(Easing::EaseOut));
+    assert_eq!(Easing::parse("Ease-In-Out"), Some(Easing::EaseInOut));
+    assert_eq!(Easing::parse("elastic"), Some(Easing::Elastic));
+    assert_eq!(Easing::parse("spring"), Some(Easing::Spring));
+    assert_eq!(Easing::parse("steps(4)"), Some(Easing::Steps(4)));
+    assert_eq!(Easing::parse("steps(0)"), None);
+    assert_eq!(Easing::parse("wobbly"), None);
+}
+
+#[test]
+fn easing_curves() {
+    let all = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Elastic,
+        Easing::Spring,
+        Easing::Steps(3),
+    ];
+    for easing in all {
+        assert!(close(easing.apply(0.0), 0.0), "{:?} does not start at 0", easing);
+        assert!(close(easing.apply(1.0), 1.0), "{:?} does not end at 1", easing);
+    }
+    assert!(close(Easing::EaseIn.apply(0.5), 0.125));
+    assert!(close(Easing::EaseOut.apply(0.5), 0.875));
+    assert!(close(Easing::EaseInOut.apply(0.5), 0.5));
+    assert!(close(Easing::Steps(4).apply(0.3), 0.25));
+    // Elastic and spring overshoot before settling
+    assert!((1..100).map(|i| Easing::Elastic.apply(i as f64 / 100.0)).any(|v| v > 1.0));
+    assert!((1..100).map(|i| Easing::Spring.apply(i as f64 / 100.0)).any(|v| v > 1.0));
+}
+
+#[test]
+fn properties_interpolate_independently() {
+    let anim = KeyframeAnimation::new(
+        "test",
+        vec![
+            Keyframe { time: 1.0, alpha: Some(1.0), offset_x: Some(0.0), ..Keyframe::default() },
+            Keyframe { time: 0.0, alpha: Some(0.0), ..Keyframe::default() },
+            Keyframe { time: 0.5, offset_x: Some(-100.0), ..Keyframe::default() },
+        ],
+        false,
+    );
+    assert_eq!(anim.duration(), 1.0);
+
+    let s = anim.sample(0.25);
+    assert!(close(s.alpha, 0.25));
+    // Before its first keyframe a property holds that keyframe's value
+    assert!(close(s.offset_x, -100.0));
+    assert!(close(anim.sample(0.75).offset_x, -50.0));
+    // Unset properties keep their defaults; the end is held
+    assert_eq!((s.scale, s.rotation, s.offset_y), (1.0, 0.0, 0.0));
+    assert!(close(anim.sample(5.0).alpha, 1.0));
+}
+
+#[test]
+fn easing_applies_to_the_segment_it_starts() {
+    let anim = KeyframeAnimation::new(
+        "test",
+        vec![
+            Keyframe {
+                time: 0.0,
+                scale: Some(0.0),
+                easing: Easing::Steps(2),
+                ..Keyframe::default()
+            },
+            Keyframe { time: 1.0, scale: Some(1.0), ..Keyframe::default() },
+            Keyframe { time: 2.0, scale: Some(0.0), ..Keyframe::default() },
+        ],
+        true,
+    );
+    assert!(close(anim.sample(0.4).scale, 0.0));
+    assert!(close(anim.sample(0.6).scale, 0.5));
+    assert!(close(anim.sample(1.5).scale, 0.5));
+    // Repeating animations wrap around
+    assert!(close(anim.sample(2.6).scale, 0.5));
+}
+
+#[test]
+fn animations_are_parsed_and_referenced_by_name() {
+    let config = AppConfig::load_from(&fixture("animations.toml")).unwrap();
+    assert_eq!(config.animations.len(), 3);
+
+    let Animation::Custom(pop) = &config.signals[0].animation else {
+        panic!("expected a custom animation, got {:?}", config.signals[0].animation);
+    };
+    assert_eq!(pop.name, "pop");
+    assert_eq!(pop.keyframes.len(), 3);
+    assert_eq!(pop.keyframes[0].easing, Easing::EaseOut);
+    assert_eq!(pop.keyframes[1].easing, Easing::Spring);
+    assert!(config.animations["wobble"].repeat);
+
+    assert_eq!(config.signals[1].animation, Animation::Bounce);
+    assert!(matches!(config.signals[2].animation, Animation::Custom(_)));
+}
+
+#[test]
+fn unknown_easing_is_an_error() {
+    let error = AppConfig::load_from(&fixture("bad_animation.toml")).unwrap_err();
+    assert!(matches!(error, ConfigError::InvalidAnimation { .. }), "{}", error);
+}
+
+#[test]
+fn draw_state_follows_keyframes() {
+    let config = AppConfig::load_from(&fixture("animations.toml")).unwrap();
+    let animation = config.find_animation("pop");
+    let fps = 30.0;
+
+    let mut state = DrawState::default();
+    state.tick(&animation, 60.0, fps);
+    assert!(state.alpha > 0.0 && state.alpha < 0.5);
+    assert!(state.scale > 0.5 && state.scale < 1.0);
+
+    for _ in 1..30 {
+        state.tick(&animation, 60.0, fps);
+    }
+    assert_eq!((state.alpha, state.scale), (1.0, 1.0));
+
+    // Built-ins leave scale and rotation alone
+    state.tick(&Animation::Fade, 60.0, fps);
+    assert_eq!((state.scale, state.rotation), (1.0, 0.0));
+}
+
+#[test]
+fn enter_idle_and_exit_animations_are_parsed() {
+    let config = AppConfig::load_from(&fixture("animations.toml")).unwrap();
+    let signal = &config.signals[3];
+    assert!(matches!(&signal.animation_in, Animation::Custom(a) if a.name == "pop"));
+    // animation_idle takes precedence over animation
+    assert!(matches!(&signal.animation, Animation::Custom(a) if a.name == "wobble"));
+    assert_eq!(signal.animation_out, Animation::Fade);
+
+    assert_eq!(config.signals[0].animation_in, Animation::None);
+    assert_eq!(config.signals[0].animation_out, Animation::None);
+}
+
+#[test]
+fn phases_run_enter_then_idle_then_exit() {
+    let config = AppConfig::load_from(&fixture("animations.toml")).unwrap();
+    let signal = &config.signals[3];
+    let fps = 30.0;
+
+    let mut state = DrawState::default();
+    state.start(signal);
+    assert_eq!(state.phase, Phase::Enter);
+    state.tick_signal(signal, fps);
+    assert!(state.alpha < 0.5 && state.scale < 1.0);
+
+    // pop lasts 0.5s
+    for _ in 1..15 {
+        state.tick_signal(signal, fps);
+    }
+    assert_eq!(state.phase, Phase::Idle);
+    assert!(state.rotation != 0.0);
+
+    assert!(state.start_exit(signal));
+    let mut alphas = Vec::new();
+    while state.phase == Phase::Exit {
+        state.tick_signal(signal, fps);
+        alphas.push(state.alpha);
+    }
+    // Built-in fade plays backwards: a fade out
+    assert!(alphas.windows(2).all(|w| w[1] <= w[0]));
+    assert_eq!(state.phase, Phase::Done);
+    assert!(!state.visible);
+}
+
+#[test]
+fn no_exit_animation_hides_immediately() {
+    let config = AppConfig::load_from(&fixture("animations.toml")).unwrap();
+    let mut state = DrawState::default();
+    state.start(&config.signals[0]);
+    assert_eq!(state.phase, Phase::Idle);
+    assert!(!state.start_exit(&config.signals[0]));
+    assert_eq!(state.phase, Phase::Idle);
+}
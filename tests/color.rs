@@ -0,0 +1,112 @@
+//! Colour string parsing and colour values in the config file.
+
+use inno::color::{parse_color, ramp_color};
+use inno::config::{AppConfig, ConfigError};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn assert_color(s: &str, expected: (f64, f64, f64, f64)) {
+    let c = parse_color(s).unwrap_or_else(|| panic!("'{}' did not parse", s));
+    let close = |a: f64, b: f64| (a - b).abs() < 0.005;
+    assert!(
+        close(c.0, expected.0)
+            && close(c.1, expected.1)
+            && close(c.2, expected.2)
+            && close(c.3, expected.3),
+        "'{}' parsed as {:?}, expected {:?}",
+        s,
+        c,
+        expected
+    );
+}
+
+#[test]
+fn hex_colors() {
+    assert_color("#f80", (1.0, 0.533, 0.0, 1.0));
+    assert_color("#f808", (1.0, 0.533, 0.0, 0.533));
+    assert_color("#FF8800", (1.0, 0.533, 0.0, 1.0));
+    assert_color("#ff880080", (1.0, 0.533, 0.0, 0.502));
+    assert_eq!(parse_color("#ff88"), Some((1.0, 1.0, 136.0 / 255.0, 136.0 / 255.0)));
+    assert_eq!(parse_color("#ff880"), None);
+    assert_eq!(parse_color("#gg0000"), None);
+}
+
+#[test]
+fn functional_colors() {
+    assert_color("rgb(255, 128, 0)", (1.0, 0.502, 0.0, 1.0));
+    assert_color("rgba(255, 128, 0, 0.5)", (1.0, 0.502, 0.0, 0.5));
+    assert_color("rgb(100% 50% 0% / 25%)", (1.0, 0.5, 0.0, 0.25));
+    assert_color("hsl(120, 100%, 50%)", (0.0, 1.0, 0.0, 1.0));
+    assert_color("hsl(0deg 0% 50%)", (0.5, 0.5, 0.5, 1.0));
+    assert_color("HSLA(240, 100%, 25%, 0.5)", (0.0, 0.0, 0.5, 0.5));
+    assert_color("hsl(-120, 100%, 50%)", (0.0, 0.0, 1.0, 1.0));
+    assert_eq!(parse_color("rgb(1, 2)"), None);
+    assert_eq!(parse_color("hsl(10, 20, 30)"), None);
+    assert_eq!(parse_color("cmyk(0, 0, 0, 0)"), None);
+}
+
+#[test]
+fn named_colors() {
+    assert_color("orange", (1.0, 0.647, 0.0, 1.0));
+    assert_color("RebeccaPurple", (0.4, 0.2, 0.6, 1.0));
+    assert_color(" white ", (1.0, 1.0, 1.0, 1.0));
+    assert_color("transparent", (0.0, 0.0, 0.0, 0.0));
+    assert_eq!(parse_color("gren"), None);
+    assert_eq!(parse_color(""), None);
+}
+
+#[test]
+fn config_accepts_color_strings() {
+    let config = AppConfig::load_from(&fixture("colors.toml")).unwrap();
+
+    assert_eq!(config.text_color, (1.0, 1.0, 1.0, 1.0));
+    assert_eq!(config.bg_color, (20.0 / 255.0, 20.0 / 255.0, 30.0 / 255.0, 0.8));
+    // [colors] names work in every colour field, and may be strings themselves
+    assert_eq!(config.border_color, (1.0, 136.0 / 255.0, 0.0, 1.0));
+    assert_eq!(config.bar.low_color, parse_color("crimson"));
+    assert_eq!(config.bar.high_color, Some((0.0, 1.0, 0.0, 1.0)));
+    assert_eq!(config.bar.track_color, (1.0, 1.0, 1.0, 0.2));
+    assert!(config.gradient.is_some());
+
+    assert_eq!(config.signals.len(), 3);
+    assert_eq!(config.signals[0].color, parse_color("orange").unwrap());
+    assert_eq!(config.signals[0].bg_color, parse_color("rebeccapurple"));
+    assert_eq!(config.signals[1].color, (0.0, 1.0, 0.0, 1.0));
+}
+
+#[test]
+fn unknown_color_is_an_error() {
+    // The whole file fails, so a reload can keep the previous config
+    let error = AppConfig::load_from(&fixture("bad_color.toml")).unwrap_err();
+    assert!(matches!(&error, ConfigError::InvalidColor { value, .. } if value == "gren"));
+
+    // The legacy format too, rather than falling back to white
+    let error = AppConfig::load_from(&fixture("bad_color.conf")).unwrap_err();
+    assert!(matches!(&error, ConfigError::InvalidColor { value, .. } if value == "gren"));
+}
+
+#[test]
+fn color_ramp_interpolates_between_stops() {
+    let (red, green) = ((1.0, 0.0, 0.0, 1.0), (0.0, 1.0, 0.0, 1.0));
+    let ramp = [(20.0, red), (80.0, green)];
+    assert_eq!(ramp_color(&ramp, 50.0), Some((0.5, 0.5, 0.0, 1.0)));
+    // The end colours hold outside the ramp
+    assert_eq!(ramp_color(&ramp, 0.0), Some(red));
+    assert_eq!(ramp_color(&ramp, 100.0), Some(green));
+    assert_eq!(ramp_color(&[], 50.0), None);
+}
+
+#[test]
+fn config_color_ramp_is_sorted_and_resolved() {
+    let config = AppConfig::load_from(&fixture("colors.toml")).unwrap();
+    let signal = &config.signals[2];
+    let ramp = signal.color_ramp.as_ref().expect("color_ramp");
+    let positions: Vec<f64> = ramp.iter().map(|stop| stop.0).collect();
+    assert_eq!(positions, [0.0, 30.0, 80.0]);
+    // [colors] names resolve inside the ramp
+    assert_eq!(ramp[1].1, parse_color("orange").unwrap());
+    assert_eq!(signal.color, config.text_color);
+}
@@ -0,0 +1,136 @@
+//! Config file parsing: icon lists, `[layout]`, `[appearance]`, per-signal
+//! overrides and `[pointer]`, against the fixtures in `tests/fixtures/`.
+
+use inno::config::{
+    Align, Anchor, AppConfig, ClickAction, CornerRadius, Direction, Element, Gradient, Icon,
+    LayoutItem, Padding, Shadow,
+};
+use std::path::PathBuf;
+
+/// Load `tests/fixtures/<name>.toml`
+fn load(name: &str) -> AppConfig {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.toml", name));
+    AppConfig::load_from(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+#[test]
+fn icon_list_is_parsed() {
+    let config = load("animated_icon");
+
+    let charging = &config.signals[0];
+    assert_eq!(charging.icon_frames.len(), 4);
+    assert_eq!(charging.icon, Icon::Glyph("◔".to_string()));
+    assert!(charging.has_animated_icon());
+    assert_eq!(charging.icon_at(0.3), &Icon::Glyph("◑".to_string()));
+    // Wraps around after the last frame
+    assert_eq!(charging.icon_at(1.1), &Icon::Glyph("◔".to_string()));
+
+    let full = &config.signals[1];
+    assert!(!full.has_animated_icon());
+    assert_eq!(full.icon_at(5.0), &Icon::Glyph("●".to_string()));
+}
+
+#[test]
+fn layout_config_is_parsed() {
+    let config = load("layout");
+
+    assert_eq!(config.layout.padding, Padding { top: 8.0, right: 12.0, bottom: 8.0, left: 12.0 });
+    assert_eq!(config.layout.spacing, 6.0);
+    assert_eq!(config.layout.min_width, Some(200.0));
+    assert_eq!(config.animation_margin, 30.0);
+    assert_eq!(config.timestamp_format, "%H:%M:%S");
+    let LayoutItem::Box(inner) = &config.layout.items[1] else {
+        panic!("expected a nested box, got {:?}", config.layout.items[1]);
+    };
+    // Nested boxes run across their parent and inherit its spacing
+    assert_eq!(inner.direction, Direction::Column);
+    assert_eq!(inner.spacing, 6.0);
+    assert_eq!(inner.align, Align::Start);
+    assert_eq!(inner.items.len(), 3);
+    assert_eq!(config.layout.items[2], LayoutItem::Element(Element::Timestamp));
+
+    assert_eq!(config.signals[0].body.as_deref(), Some("{percent}% remaining"));
+    assert_eq!(config.signals[0].layout, None);
+    // Signal layouts start from the global one; unknown elements are dropped
+    let layout = config.signals[1].layout.as_ref().unwrap();
+    assert_eq!(layout.direction, Direction::Column);
+    assert_eq!(layout.width, Some(240.0));
+    assert_eq!(layout.padding, config.layout.padding);
+    assert_eq!(layout.items, vec![LayoutItem::Element(Element::Title)]);
+}
+
+#[test]
+fn appearance_config_is_parsed() {
+    let config = load("appearance");
+
+    assert_eq!(
+        config.border_radius,
+        CornerRadius { top_left: 12.0, top_right: 0.0, bottom_right: 12.0, bottom_left: 0.0 }
+    );
+    assert_eq!(config.border_width, 2.0);
+    assert_eq!(config.border_color, (1.0, 1.0, 1.0, 0.5));
+    let Some(Gradient::Linear { angle, stops }) = &config.gradient else {
+        panic!("expected a linear gradient, got {:?}", config.gradient);
+    };
+    assert_eq!(*angle, 45.0);
+    assert_eq!(stops.len(), 3);
+    assert_eq!(stops[1], (0.5, (0.0, 0.2, 0.4, 0.9)));
+    assert_eq!(
+        config.shadow,
+        Some(Shadow { color: (0.0, 0.0, 0.0, 0.5), blur: 10.0, offset_x: 0.0, offset_y: 3.0 })
+    );
+
+    let signal = &config.signals[0];
+    assert_eq!(signal.border_width, Some(3.0));
+    assert!(matches!(signal.gradient, Some(Some(Gradient::Radial { .. }))));
+    assert_eq!(signal.shadow.flatten().map(|s| (s.blur, s.offset_y)), Some((16.0, 2.0)));
+
+    // `false` turns the global ones off rather than falling back to them
+    let plain = &config.signals[1];
+    assert_eq!(plain.gradient, Some(None));
+    assert_eq!(plain.shadow, Some(None));
+}
+
+#[test]
+fn signal_overrides_are_parsed() {
+    let config = load("signal_overrides");
+
+    let critical = &config.signals[0];
+    assert_eq!(critical.bg_color, Some((0.6, 0.0, 0.0, 0.9)));
+    assert_eq!(critical.font.as_deref(), Some("DejaVu Serif"));
+    assert_eq!(critical.font_size, Some(32.0));
+    assert_eq!(
+        critical.border_radius,
+        Some(CornerRadius { top_left: 0.0, top_right: 16.0, bottom_right: 0.0, bottom_left: 16.0 })
+    );
+    assert_eq!(critical.anchor, Some(Anchor::parse("center,center,0")));
+    assert_ne!(critical.anchor.as_ref(), Some(&config.anchor));
+
+    // Signals without overrides follow the global settings
+    let low = &config.signals[1];
+    assert_eq!(
+        (&low.bg_color, &low.font, low.font_size, &low.border_radius, &low.anchor),
+        (&None, &None, None, &None, &None)
+    );
+}
+
+#[test]
+fn pointer_config_is_parsed() {
+    let defaults = AppConfig::default().pointer;
+    assert_eq!(defaults.left_click, ClickAction::Dismiss);
+    assert_eq!(defaults.middle_click, ClickAction::DismissAll);
+    assert_eq!(defaults.right_click, ClickAction::None);
+    assert!(defaults.hover_pause);
+
+    let pointer = load("pointer").pointer;
+    assert_eq!(pointer.left_click, ClickAction::None);
+    assert_eq!(pointer.middle_click, ClickAction::Dismiss);
+    assert_eq!(
+        pointer.right_click,
+        ClickAction::Command("notify-send 'Battery' \"$INNO_TEXT\"".to_string())
+    );
+    assert!(!pointer.hover_pause);
+    assert_eq!(ClickAction::parse("Dismiss_All"), ClickAction::DismissAll);
+}
@@ -0,0 +1,90 @@
+//! Icon value parsing and freedesktop icon theme lookup against the
+//! fixture themes in `tests/fixtures/icons/`.
+
+use inno::config::Icon;
+use inno::icons::{find_icon_in, load_icon};
+use std::path::PathBuf;
+
+fn base_dirs() -> Vec<PathBuf> {
+    vec![PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/icons")]
+}
+
+fn lookup(icon: &str, size: u32, theme: &str) -> Option<String> {
+    let path = find_icon_in(icon, size, 1, theme, &base_dirs())?;
+    let base = &base_dirs()[0];
+    Some(path.strip_prefix(base).unwrap().to_string_lossy().into_owned())
+}
+
+#[test]
+fn parse_icon_values() {
+    assert_eq!(Icon::parse(""), Icon::None);
+    assert_eq!(Icon::parse("󰂃"), Icon::Glyph("󰂃".to_string()));
+    assert_eq!(Icon::parse("!"), Icon::Glyph("!".to_string()));
+    assert_eq!(Icon::parse("/tmp/low.png"), Icon::File(PathBuf::from("/tmp/low.png")));
+    assert_eq!(Icon::parse("icons/low.SVG"), Icon::File(PathBuf::from("icons/low.SVG")));
+    assert_eq!(
+        Icon::parse("battery-caution-symbolic"),
+        Icon::Named("battery-caution-symbolic".to_string())
+    );
+    assert_eq!(Icon::parse("theme:firefox"), Icon::Named("firefox".to_string()));
+    // Short text stays text rather than becoming a theme lookup
+    for text in ["OK", "BAT", "AC", "-5"] {
+        assert_eq!(Icon::parse(text), Icon::Glyph(text.to_string()));
+    }
+    assert!(Icon::parse("battery-caution-symbolic").is_symbolic());
+    assert!(!Icon::parse("battery-full").is_symbolic());
+}
+
+#[test]
+fn exact_size_is_preferred() {
+    assert_eq!(
+        lookup("battery-full", 16, "hicolor").as_deref(),
+        Some("hicolor/16x16/status/battery-full.svg")
+    );
+    // 48x48 is a Threshold directory covering 46-50
+    assert_eq!(
+        lookup("battery-full", 47, "hicolor").as_deref(),
+        Some("hicolor/48x48/status/battery-full.svg")
+    );
+    // Only the scalable directory covers 100
+    assert_eq!(
+        lookup("battery-full", 100, "hicolor").as_deref(),
+        Some("hicolor/scalable/status/battery-full.svg")
+    );
+}
+
+#[test]
+fn closest_size_is_used_without_exact_match() {
+    assert_eq!(
+        lookup("battery-caution-symbolic", 24, "hicolor").as_deref(),
+        Some("hicolor/scalable/status/battery-caution-symbolic.svg")
+    );
+}
+
+#[test]
+fn themes_fall_back_to_parents() {
+    assert_eq!(
+        lookup("battery-caution-symbolic", 24, "Custom").as_deref(),
+        Some("Custom/24x24/status/battery-caution-symbolic.svg")
+    );
+    // Not in Custom, so found through Inherits=hicolor
+    assert_eq!(
+        lookup("battery-full", 16, "Custom").as_deref(),
+        Some("hicolor/16x16/status/battery-full.svg")
+    );
+    // Unknown themes still fall back to hicolor
+    assert_eq!(
+        lookup("battery-full", 16, "Missing").as_deref(),
+        Some("hicolor/16x16/status/battery-full.svg")
+    );
+    assert_eq!(lookup("no-such-icon", 16, "Custom"), None);
+}
+
+#[test]
+fn icons_load_at_output_scale() {
+    let path = base_dirs()[0].join("hicolor/scalable/status/battery-full.svg");
+    let icon = Icon::File(path);
+    let normal = load_icon(&icon, 16.0, 1, "hicolor").unwrap();
+    let hidpi = load_icon(&icon, 16.0, 2, "hicolor").unwrap();
+    assert_eq!(hidpi.height(), normal.height() * 2);
+}
@@ -0,0 +1,127 @@
+//! Notifications requested over the control interface: building a signal
+//! from a `ShowEx` request and a configured `[[signal]]`.
+
+use inno::config::{Animation, AppConfig, HAnchor, Icon, VAnchor};
+use inno::notification::{Priority, ShowRequest};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+#[test]
+fn plain_request_uses_defaults() {
+    let config = AppConfig::default();
+    let request = ShowRequest { message: Some("Hello".to_string()), ..ShowRequest::default() };
+    let signal = request.to_signal(&config).unwrap();
+    assert_eq!(signal.color, config.text_color);
+    assert_eq!(signal.icon, Icon::None);
+    assert_eq!(signal.duration, 5);
+    assert!(!signal.bar);
+    assert_eq!(request.text(&config, &signal), "Hello");
+}
+
+#[test]
+fn request_fields_override_defaults() {
+    let config = AppConfig::load_from(&fixture("colors.toml")).unwrap();
+    let request = ShowRequest {
+        message: Some("Volume".to_string()),
+        icon: Some("󰕾".to_string()),
+        color: Some("warning".to_string()),
+        animation: Some("bounce".to_string()),
+        percent: Some(40.0),
+        position: Some("left,top".to_string()),
+        duration: Some(2),
+        ..ShowRequest::default()
+    };
+    let signal = request.to_signal(&config).unwrap();
+    assert_eq!(signal.icon, Icon::Glyph("󰕾".to_string()));
+    // [colors] names resolve
+    assert_eq!(signal.color, (1.0, 165.0 / 255.0, 0.0, 1.0));
+    assert_eq!(signal.animation, Animation::Bounce);
+    assert!(signal.bar);
+    assert_eq!(signal.duration, 2);
+    let anchor = signal.anchor.clone().unwrap();
+    assert_eq!((anchor.h, anchor.v), (HAnchor::Left, VAnchor::Top));
+    assert_eq!(request.text(&config, &signal), "Volume 40%");
+}
+
+#[test]
+fn named_signal_is_the_starting_point() {
+    let config = AppConfig::load_from(&fixture("animations.toml")).unwrap();
+    let request = ShowRequest {
+        signal: Some("Low Battery!".to_string()),
+        percent: Some(12.0),
+        ..ShowRequest::default()
+    };
+    let signal = request.to_signal(&config).unwrap();
+    assert!(matches!(&signal.animation, Animation::Custom(a) if a.name == "pop"));
+    assert_eq!(request.text(&config, &signal), "Low Battery! 12%");
+
+    // Custom animations are found by name too
+    let request = ShowRequest {
+        signal: Some("Charging".to_string()),
+        animation: Some("wobble".to_string()),
+        ..ShowRequest::default()
+    };
+    let signal = request.to_signal(&config).unwrap();
+    assert!(matches!(&signal.animation, Animation::Custom(a) if a.name == "wobble"));
+}
+
+#[test]
+fn bad_fields_are_errors() {
+    let config = AppConfig::default();
+    let bad = [
+        ShowRequest { signal: Some("Nope".to_string()), ..ShowRequest::default() },
+        ShowRequest { color: Some("gren".to_string()), ..ShowRequest::default() },
+        ShowRequest { animation: Some("wiggle".to_string()), ..ShowRequest::default() },
+    ];
+    for request in bad {
+        assert!(request.to_signal(&config).is_err(), "{:?} was accepted", request);
+    }
+}
+
+#[test]
+fn priorities() {
+    assert_eq!(Priority::parse("Critical"), Some(Priority::Critical));
+    assert_eq!(Priority::parse("0"), Some(Priority::Low));
+    assert_eq!(Priority::parse("meh"), None);
+    assert!(Priority::Low < Priority::Normal && Priority::Normal < Priority::Critical);
+}
+
+#[test]
+fn update_keeps_unset_fields() {
+    let config = AppConfig::default();
+    let mut request = ShowRequest {
+        message: Some("Copying".to_string()),
+        icon: Some("󰆏".to_string()),
+        percent: Some(10.0),
+        priority: Some(Priority::Critical),
+        ..ShowRequest::default()
+    };
+    request.merge(ShowRequest { percent: Some(42.0), ..ShowRequest::default() });
+    assert_eq!(request.message.as_deref(), Some("Copying"));
+    assert_eq!(request.priority, Some(Priority::Critical));
+    let signal = request.to_signal(&config).unwrap();
+    assert_eq!(request.text(&config, &signal), "Copying 42%");
+}
+
+#[test]
+fn request_from_json() {
+    let request: ShowRequest = serde_json::from_str(
+        r#"{"message": "Copying", "percent": 42, "duration": 3, "priority": "urgent"}"#,
+    )
+    .unwrap();
+    assert_eq!(request.message.as_deref(), Some("Copying"));
+    assert_eq!(request.percent, Some(42.0));
+    assert_eq!(request.priority, Some(Priority::Critical));
+
+    // Levels work as over DBus
+    let request: ShowRequest = serde_json::from_str(r#"{"priority": 0}"#).unwrap();
+    assert_eq!(request.priority, Some(Priority::Low));
+    assert!(serde_json::from_str::<ShowRequest>(r#"{"priority": 3}"#).is_err());
+
+    // Typos are errors rather than silently ignored
+    assert!(serde_json::from_str::<ShowRequest>(r#"{"mesage": "Hi"}"#).is_err());
+    assert!(serde_json::from_str::<ShowRequest>(r#"{"priority": "meh"}"#).is_err());
+}
@@ -0,0 +1,682 @@
+//! Golden-image regression tests for the notification renderer.
+//!
+//! Each test renders a notification through `DrawState::tick` and
+//! `draw_with_signal` and compares it against a reference PNG in
+//! `tests/golden/`. After an intentional visual change, regenerate the
+//! references with:
+//!
+//!     INNO_BLESS=1 cargo test --test render
+
+use inno::animation::{Easing, Keyframe, KeyframeAnimation};
+use inno::config::{
+    Align, Animation, AppConfig, ColorStop, CornerRadius, Direction, Element, Gradient, Icon,
+    LayoutBox, LayoutItem, Padding, Shadow, Signal,
+};
+use inno::draw::{self, DrawState, format_text};
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const FPS: f64 = 30.0;
+/// Largest per-channel difference that still counts as a matching pixel
+const CHANNEL_TOLERANCE: u8 = 16;
+/// Fraction of pixels allowed to exceed `CHANNEL_TOLERANCE` (font hinting drift)
+const PIXEL_TOLERANCE: f64 = 0.01;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name))
+}
+
+fn test_config() -> AppConfig {
+    AppConfig {
+        font: "DejaVu Sans".to_string(),
+        font_size: 18.0,
+        text_color: (1.0, 1.0, 1.0, 1.0),
+        bg_color: (0.1, 0.1, 0.1, 0.8),
+        ..AppConfig::default()
+    }
+}
+
+fn test_signal(animation: Animation) -> Signal {
+    Signal {
+        message: "Low Battery!".to_string(),
+        icon: Icon::Glyph("●".to_string()),
+        icon_size: 20.0,
+        icon_recolor: None,
+        color: (1.0, 0.65, 0.0, 1.0),
+        threshold: 25.0,
+        state_filter: "discharging".to_string(),
+        animation,
+        duration: 2,
+        sound: None,
+        bar: false,
+        ..Signal::default()
+    }
+}
+
+/// Advance a fresh `DrawState` by `frame` ticks, the way the main loop does
+fn state_at(signal: &Signal, frame: u32) -> DrawState {
+    let mut state = DrawState::default();
+    state.start(signal);
+    for _ in 0..frame {
+        state.tick_signal(signal, FPS);
+    }
+    state
+}
+
+fn render(
+    config: &AppConfig,
+    signal: Option<&Signal>,
+    percent: Option<f64>,
+    frame: u32,
+) -> cairo::ImageSurface {
+    let (icon, message) =
+        signal.map(|s| (s.icon.glyph(), s.message.as_str())).unwrap_or(("", "Hello"));
+    let text = format_text(&config.format, icon, message, percent);
+    let state = signal.map(|s| state_at(s, frame)).unwrap_or_default();
+    draw::render_to_surface(&text, config, signal, percent, &state).expect("render notification")
+}
+
+/// Copy the visible pixels of a surface, dropping any stride padding
+fn pixels(mut surface: cairo::ImageSurface) -> (i32, i32, Vec<u8>) {
+    let (w, h, stride) = (surface.width(), surface.height(), surface.stride());
+    let data = surface.data().expect("surface data");
+    let mut out = Vec::with_capacity((w * h * 4) as usize);
+    for row in 0..h {
+        let start = (row * stride) as usize;
+        out.extend_from_slice(&data[start..start + (w * 4) as usize]);
+    }
+    (w, h, out)
+}
+
+fn assert_golden(name: &str, surface: cairo::ImageSurface) {
+    let path = golden_path(name);
+
+    if std::env::var_os("INNO_BLESS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut file = File::create(&path).unwrap();
+        surface.write_to_png(&mut file).unwrap();
+        eprintln!("Wrote golden image {:?}", path);
+        return;
+    }
+    assert!(
+        path.exists(),
+        "missing golden image {}; run INNO_BLESS=1 cargo test --test render",
+        path.display()
+    );
+
+    let mut file = File::open(&path).unwrap();
+    let expected = cairo::ImageSurface::create_from_png(&mut file).expect("read golden image");
+
+    let (w, h, actual) = pixels(surface);
+    let (ew, eh, expected) = pixels(expected);
+    assert_eq!((w, h), (ew, eh), "{}: size differs from golden image", name);
+
+    let bad = actual
+        .chunks_exact(4)
+        .zip(expected.chunks_exact(4))
+        .filter(|(a, e)| a.iter().zip(e.iter()).any(|(a, e)| a.abs_diff(*e) > CHANNEL_TOLERANCE))
+        .count();
+    let ratio = bad as f64 / (w * h) as f64;
+    assert!(
+        ratio <= PIXEL_TOLERANCE,
+        "{}: {} of {} pixels differ from golden image ({:.2}%)",
+        name,
+        bad,
+        w * h,
+        ratio * 100.0
+    );
+}
+
+fn check_animation(name: &str, animation: Animation, frames: &[u32]) {
+    let config = test_config();
+    let signal = test_signal(animation);
+    for &frame in frames {
+        let surface = render(&config, Some(&signal), Some(20.0), frame);
+        assert_golden(&format!("{}_{:02}", name, frame), surface);
+    }
+}
+
+#[test]
+fn animation_none() {
+    check_animation("none", Animation::None, &[0, 30]);
+}
+
+#[test]
+fn animation_blink() {
+    // Frames 15-29 are the "off" half of the blink cycle
+    check_animation("blink", Animation::Blink, &[1, 20, 35]);
+}
+
+#[test]
+fn animation_pulse() {
+    check_animation("pulse", Animation::Pulse, &[1, 10, 20]);
+}
+
+#[test]
+fn animation_fade() {
+    check_animation("fade", Animation::Fade, &[1, 8, 30, 55]);
+}
+
+#[test]
+fn animation_slide_left() {
+    check_animation("slide_left", Animation::SlideLeft, &[1, 5, 10, 20]);
+}
+
+#[test]
+fn animation_slide_right() {
+    check_animation("slide_right", Animation::SlideRight, &[1, 5, 10, 20]);
+}
+
+#[test]
+fn animation_slide_up() {
+    check_animation("slide_up", Animation::SlideUp, &[1, 5, 20]);
+}
+
+#[test]
+fn animation_slide_down() {
+    check_animation("slide_down", Animation::SlideDown, &[1, 5, 20]);
+}
+
+#[test]
+fn animation_zoom() {
+    // Overshoots past full size before settling
+    check_animation("zoom", Animation::Zoom, &[1, 5, 7, 10]);
+}
+
+#[test]
+fn animation_shake() {
+    check_animation("shake", Animation::Shake, &[2, 5, 20]);
+}
+
+#[test]
+fn animation_typewriter() {
+    // The surface keeps the full text size while characters are revealed
+    check_animation("typewriter", Animation::Typewriter, &[1, 5, 30]);
+}
+
+#[test]
+fn animation_bounce() {
+    check_animation("bounce", Animation::Bounce, &[1, 4, 8, 15]);
+}
+
+#[test]
+fn animation_keyframes() {
+    let keyframes = vec![
+        Keyframe {
+            time: 0.0,
+            alpha: Some(0.2),
+            scale: Some(0.6),
+            rotation: Some(-10.0),
+            easing: Easing::EaseOut,
+            ..Keyframe::default()
+        },
+        Keyframe {
+            time: 0.5,
+            alpha: Some(1.0),
+            scale: Some(1.0),
+            rotation: Some(0.0),
+            ..Keyframe::default()
+        },
+    ];
+    let animation = Animation::Custom(Arc::new(KeyframeAnimation::new("spin", keyframes, false)));
+    check_animation("keyframes", animation, &[1, 5, 15]);
+}
+
+#[test]
+fn blink_off_renders_empty_surface() {
+    let config = test_config();
+    let signal = test_signal(Animation::Blink);
+    let surface = render(&config, Some(&signal), Some(20.0), 20);
+    assert_eq!((surface.width(), surface.height()), (1, 1));
+}
+
+#[test]
+fn gradient_background() {
+    let config = AppConfig { gradient: Some(Gradient::Darken), ..test_config() };
+    let signal = test_signal(Animation::None);
+    assert_golden("gradient", render(&config, Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn rounded_corners() {
+    let config = AppConfig { border_radius: CornerRadius::all(12.0), ..test_config() };
+    let signal = test_signal(Animation::None);
+    assert_golden("border_radius", render(&config, Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn gradient_with_rounded_corners() {
+    let config = AppConfig {
+        gradient: Some(Gradient::Darken),
+        border_radius: CornerRadius::all(8.0),
+        ..test_config()
+    };
+    let signal = test_signal(Animation::None);
+    assert_golden("gradient_border_radius", render(&config, Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn icon_and_text() {
+    let config = AppConfig { format: "{icon} {message}".to_string(), ..test_config() };
+    let signal = Signal { icon_size: 28.0, ..test_signal(Animation::None) };
+    assert_golden("icon_large", render(&config, Some(&signal), None, 0));
+}
+
+#[test]
+fn no_signal_uses_text_color() {
+    let config = test_config();
+    assert_golden("no_signal", render(&config, None, Some(42.0), 0));
+}
+
+#[test]
+fn missing_percent_is_removed() {
+    assert_eq!(format_text("{message} {percent}%", "", "Connected", None), "Connected");
+    assert_eq!(format_text("{percent} {message}", "", "Connected", None), "Connected");
+    assert_eq!(format_text("{message} {percent}%", "", "Low", Some(19.6)), "Low 20%");
+
+    let config = test_config();
+    let signal =
+        Signal { message: "Headset connected".to_string(), ..test_signal(Animation::None) };
+    assert_golden("missing_percent", render(&config, Some(&signal), None, 0));
+}
+
+#[test]
+fn wrapped_text() {
+    let config = AppConfig { max_width: Some(120.0), ..test_config() };
+    let signal = Signal {
+        message: "Battery is running low, plug in the charger".to_string(),
+        ..test_signal(Animation::None)
+    };
+    let single_line = render(&test_config(), Some(&signal), Some(20.0), 0);
+    let wrapped = render(&config, Some(&signal), Some(20.0), 0);
+    assert!(wrapped.height() > single_line.height(), "text did not wrap onto several lines");
+    assert_golden("wrapped", wrapped);
+}
+
+#[test]
+fn ellipsized_text() {
+    let config = AppConfig { max_width: Some(120.0), max_lines: Some(1), ..test_config() };
+    let signal = Signal {
+        message: "Battery is running low, plug in the charger".to_string(),
+        ..test_signal(Animation::None)
+    };
+    let surface = render(&config, Some(&signal), Some(20.0), 0);
+    assert_eq!(surface.height(), render(&test_config(), Some(&signal), None, 0).height());
+    assert_golden("ellipsized", surface);
+}
+
+#[test]
+fn markup_message() {
+    let signal = Signal {
+        message: "<b>Low</b> <span foreground=\"red\">Battery</span>".to_string(),
+        ..test_signal(Animation::None)
+    };
+    assert_golden("markup", render(&test_config(), Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn invalid_markup_falls_back_to_plain_text() {
+    let signal =
+        Signal { message: "Volume < 5 & muted".to_string(), ..test_signal(Animation::None) };
+    let plain = AppConfig { markup: false, ..test_config() };
+    let (_, _, with_markup) = pixels(render(&test_config(), Some(&signal), None, 0));
+    let (_, _, without_markup) = pixels(render(&plain, Some(&signal), None, 0));
+    assert_eq!(with_markup, without_markup);
+}
+
+#[test]
+fn percentage_bar() {
+    let config = test_config();
+    let signal = Signal { bar: true, ..test_signal(Animation::None) };
+    let without_bar = render(&config, Some(&test_signal(Animation::None)), Some(40.0), 0);
+    let with_bar = render(&config, Some(&signal), Some(40.0), 0);
+    assert_eq!(with_bar.width(), without_bar.width() + config.bar.width as i32 + 10);
+    assert_golden("bar", with_bar);
+}
+
+#[test]
+fn percentage_bar_needs_percent() {
+    let config = test_config();
+    let signal = Signal { bar: true, ..test_signal(Animation::None) };
+    let plain = render(&config, Some(&test_signal(Animation::None)), None, 0);
+    let surface = render(&config, Some(&signal), None, 0);
+    assert_eq!(surface.width(), plain.width());
+}
+
+#[test]
+fn percentage_bar_interpolated_color() {
+    let mut config = test_config();
+    config.bar.rounded = false;
+    config.bar.height = 12.0;
+    config.bar.low_color = Some((1.0, 0.0, 0.0, 1.0));
+    config.bar.high_color = Some((0.0, 1.0, 0.0, 1.0));
+    let signal = Signal { bar: true, ..test_signal(Animation::None) };
+    assert_golden("bar_low", render(&config, Some(&signal), Some(10.0), 0));
+    assert_golden("bar_high", render(&config, Some(&signal), Some(90.0), 0));
+}
+
+#[test]
+fn color_ramp_by_percent() {
+    let signal = Signal {
+        bar: true,
+        color_ramp: Some(vec![
+            (0.0, (1.0, 0.0, 0.0, 1.0)),
+            (30.0, (1.0, 0.65, 0.0, 1.0)),
+            (80.0, (0.0, 1.0, 0.0, 1.0)),
+        ]),
+        ..test_signal(Animation::None)
+    };
+    let config = test_config();
+    for percent in [10.0, 55.0, 90.0] {
+        let surface = render(&config, Some(&signal), Some(percent), 0);
+        assert_golden(&format!("color_ramp_{:.0}", percent), surface);
+    }
+}
+
+#[test]
+fn global_color_ramp_applies_to_signals_without_one() {
+    let mut config = test_config();
+    config.color_ramp = Some(vec![(0.0, (1.0, 0.0, 0.0, 1.0)), (100.0, (0.0, 0.0, 1.0, 1.0))]);
+    let signal = test_signal(Animation::None);
+    let (_, _, ramped) = pixels(render(&config, Some(&signal), Some(50.0), 0));
+    let (_, _, plain) = pixels(render(&test_config(), Some(&signal), Some(50.0), 0));
+    assert_ne!(ramped, plain);
+}
+
+fn fixture_icon(name: &str) -> Icon {
+    Icon::File(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/icons/hicolor/scalable/status")
+            .join(name),
+    )
+}
+
+#[test]
+fn svg_file_icon() {
+    let signal = Signal {
+        icon: fixture_icon("battery-full.svg"),
+        icon_size: 32.0,
+        ..test_signal(Animation::None)
+    };
+    assert_golden("icon_svg", render(&test_config(), Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn symbolic_icon_is_recolored() {
+    let signal = Signal {
+        icon: fixture_icon("battery-caution-symbolic.svg"),
+        icon_size: 24.0,
+        ..test_signal(Animation::None)
+    };
+    let plain = Signal { icon_recolor: Some(false), ..signal.clone() };
+    let (_, _, recolored) = pixels(render(&test_config(), Some(&signal), Some(20.0), 0));
+    let (_, _, original) = pixels(render(&test_config(), Some(&plain), Some(20.0), 0));
+    assert_ne!(recolored, original);
+    assert_golden("icon_symbolic", render(&test_config(), Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn animated_icon() {
+    let frames: Vec<Icon> = ["◔", "◑", "◕"].iter().map(|g| Icon::Glyph(g.to_string())).collect();
+    let signal = Signal {
+        icon: frames[0].clone(),
+        icon_frames: frames,
+        icon_interval: 0.5,
+        ..test_signal(Animation::None)
+    };
+    let config = test_config();
+    // 30 fps: frame 16 is the second icon, frame 31 the third
+    for frame in [1, 16, 31] {
+        let surface = render(&config, Some(&signal), Some(20.0), frame);
+        assert_golden(&format!("animated_icon_{:02}", frame), surface);
+    }
+}
+
+/// Icon beside a column of title, body and bar
+fn stacked_layout() -> LayoutBox {
+    let column = LayoutBox {
+        direction: Direction::Column,
+        items: vec![
+            LayoutItem::Element(Element::Title),
+            LayoutItem::Element(Element::Body),
+            LayoutItem::Element(Element::Bar),
+        ],
+        padding: Padding::default(),
+        spacing: 4.0,
+        align: Align::Start,
+        ..LayoutBox::default()
+    };
+    LayoutBox {
+        items: vec![LayoutItem::Element(Element::Icon), LayoutItem::Box(column)],
+        padding: Padding { top: 12.0, right: 16.0, bottom: 12.0, left: 16.0 },
+        ..LayoutBox::default()
+    }
+}
+
+#[test]
+fn stacked_layout_with_body_and_bar() {
+    let config = AppConfig { layout: stacked_layout(), ..test_config() };
+    let signal = Signal {
+        icon_size: 32.0,
+        body: Some("{percent}% remaining".to_string()),
+        bar: true,
+        ..test_signal(Animation::None)
+    };
+    assert_golden("layout_stacked", render(&config, Some(&signal), Some(20.0), 0));
+}
+
+#[test]
+fn signal_layout_overrides_global() {
+    let config = test_config();
+    let signal = Signal {
+        body: Some("{percent}% remaining".to_string()),
+        bar: true,
+        layout: Some(stacked_layout()),
+        ..test_signal(Animation::None)
+    };
+    let with_global = AppConfig { layout: stacked_layout(), ..test_config() };
+    let (_, _, expected) = pixels(render(&with_global, Some(&signal), Some(20.0), 0));
+    let (_, _, actual) = pixels(render(&config, Some(&signal), Some(20.0), 0));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn fixed_size_layout() {
+    let layout = LayoutBox {
+        width: Some(300.0),
+        height: Some(80.0),
+        justify: Align::Center,
+        ..LayoutBox::default()
+    };
+    let config = AppConfig { layout, animation_margin: 10.0, ..test_config() };
+    let surface = render(&config, Some(&test_signal(Animation::None)), Some(20.0), 0);
+    assert_eq!((surface.width(), surface.height()), (300, 100));
+    assert_golden("layout_fixed", surface);
+}
+
+#[test]
+fn layout_max_width_wraps_text() {
+    let layout = LayoutBox { max_width: Some(160.0), ..LayoutBox::default() };
+    let config = AppConfig { layout, ..test_config() };
+    let signal = Signal {
+        message: "Battery is running low, plug in the charger".to_string(),
+        ..test_signal(Animation::None)
+    };
+    let single_line = render(&test_config(), Some(&signal), Some(20.0), 0);
+    let wrapped = render(&config, Some(&signal), Some(20.0), 0);
+    assert!(wrapped.width() <= 160);
+    assert!(wrapped.height() > single_line.height(), "text did not wrap onto several lines");
+}
+
+#[test]
+fn timestamp_element() {
+    let layout = LayoutBox {
+        items: vec![
+            LayoutItem::Element(Element::Icon),
+            LayoutItem::Element(Element::Title),
+            LayoutItem::Element(Element::Timestamp),
+        ],
+        align: Align::End,
+        ..LayoutBox::default()
+    };
+    // A format without conversions keeps the golden image stable
+    let config = AppConfig { layout, timestamp_format: "12:00".to_string(), ..test_config() };
+    assert_golden(
+        "layout_timestamp",
+        render(&config, Some(&test_signal(Animation::None)), Some(20.0), 0),
+    );
+}
+
+fn two_stop(from: (f64, f64, f64, f64), to: (f64, f64, f64, f64)) -> Vec<ColorStop> {
+    vec![(0.0, from), (1.0, to)]
+}
+
+#[test]
+fn border_with_corner_radii() {
+    let config = AppConfig {
+        border_radius: CornerRadius {
+            top_left: 16.0,
+            top_right: 0.0,
+            bottom_right: 16.0,
+            bottom_left: 4.0,
+        },
+        border_width: 3.0,
+        border_color: (1.0, 0.65, 0.0, 0.8),
+        ..test_config()
+    };
+    let signal = test_signal(Animation::None);
+    let plain = render(&test_config(), Some(&signal), Some(20.0), 0);
+    let bordered = render(&config, Some(&signal), Some(20.0), 0);
+    // The stroke stays inside the background
+    assert_eq!((bordered.width(), bordered.height()), (plain.width(), plain.height()));
+    assert_golden("border", bordered);
+}
+
+#[test]
+fn drop_shadow() {
+    let shadow = Shadow { color: (0.0, 0.0, 0.0, 0.8), blur: 12.0, offset_x: 4.0, offset_y: 4.0 };
+    let config = AppConfig {
+        bg_color: (0.2, 0.2, 0.25, 1.0),
+        border_radius: CornerRadius::all(10.0),
+        shadow: Some(shadow),
+        ..test_config()
+    };
+    let signal = test_signal(Animation::None);
+    let plain = AppConfig { shadow: None, ..config.clone() };
+    let without = render(&plain, Some(&signal), Some(20.0), 0);
+    let with = render(&config, Some(&signal), Some(20.0), 0);
+    // 12px blur: 8px room on the left, 16px on the right
+    assert_eq!(with.width(), without.width() + 24);
+    assert_eq!(with.height(), without.height());
+    assert_golden("shadow", with);
+}
+
+#[test]
+fn angled_linear_gradient() {
+    let stops =
+        vec![(0.0, (0.6, 0.1, 0.1, 0.9)), (0.5, (0.1, 0.1, 0.6, 0.9)), (1.0, (0.1, 0.5, 0.1, 0.9))];
+    let config =
+        AppConfig { gradient: Some(Gradient::Linear { angle: 30.0, stops }), ..test_config() };
+    assert_golden(
+        "gradient_linear",
+        render(&config, Some(&test_signal(Animation::None)), Some(20.0), 0),
+    );
+}
+
+#[test]
+fn radial_gradient() {
+    let stops = two_stop((0.3, 0.3, 0.3, 1.0), (0.0, 0.0, 0.0, 0.6));
+    let config = AppConfig { gradient: Some(Gradient::Radial { stops }), ..test_config() };
+    assert_golden(
+        "gradient_radial",
+        render(&config, Some(&test_signal(Animation::None)), Some(20.0), 0),
+    );
+}
+
+#[test]
+fn signal_appearance_overrides_global() {
+    let gradient = Gradient::Radial { stops: two_stop((0.5, 0.0, 0.0, 0.9), (0.1, 0.0, 0.0, 0.9)) };
+    let shadow = Shadow::default();
+    let signal = Signal {
+        gradient: Some(Some(gradient.clone())),
+        border_width: Some(2.0),
+        border_color: Some((1.0, 0.0, 0.0, 1.0)),
+        shadow: Some(Some(shadow)),
+        ..test_signal(Animation::None)
+    };
+    let global = AppConfig {
+        gradient: Some(gradient),
+        border_width: 2.0,
+        border_color: (1.0, 0.0, 0.0, 1.0),
+        shadow: Some(shadow),
+        ..test_config()
+    };
+    let (_, _, expected) =
+        pixels(render(&global, Some(&test_signal(Animation::None)), Some(20.0), 0));
+    let (_, _, actual) = pixels(render(&test_config(), Some(&signal), Some(20.0), 0));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn signal_turns_off_global_gradient_and_shadow() {
+    let global = AppConfig {
+        gradient: Some(Gradient::Darken),
+        shadow: Some(Shadow::default()),
+        ..test_config()
+    };
+    let signal =
+        Signal { gradient: Some(None), shadow: Some(None), ..test_signal(Animation::None) };
+    let (_, _, expected) =
+        pixels(render(&test_config(), Some(&test_signal(Animation::None)), Some(20.0), 0));
+    let (_, _, actual) = pixels(render(&global, Some(&signal), Some(20.0), 0));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn signal_style_overrides_global() {
+    let signal = Signal {
+        bg_color: Some((0.6, 0.0, 0.0, 0.9)),
+        font: Some("DejaVu Serif".to_string()),
+        font_size: Some(28.0),
+        border_radius: Some(CornerRadius::all(14.0)),
+        ..test_signal(Animation::None)
+    };
+    let global = AppConfig {
+        bg_color: (0.6, 0.0, 0.0, 0.9),
+        font: "DejaVu Serif".to_string(),
+        font_size: 28.0,
+        border_radius: CornerRadius::all(14.0),
+        ..test_config()
+    };
+    let surface = render(&test_config(), Some(&signal), Some(20.0), 0);
+    assert!(
+        surface.height()
+            > render(&test_config(), Some(&test_signal(Animation::None)), Some(20.0), 0).height()
+    );
+    let (_, _, expected) =
+        pixels(render(&global, Some(&test_signal(Animation::None)), Some(20.0), 0));
+    let (_, _, actual) = pixels(render(&test_config(), Some(&signal), Some(20.0), 0));
+    assert_eq!(actual, expected);
+    assert_golden("signal_style", surface);
+}
+
+#[test]
+fn background_rect_excludes_shadow_and_margin() {
+    let shadow = Shadow { color: (0.0, 0.0, 0.0, 0.8), blur: 12.0, offset_x: 4.0, offset_y: 4.0 };
+    let config = AppConfig { shadow: Some(shadow), animation_margin: 20.0, ..test_config() };
+    let signal = test_signal(Animation::None);
+    let state = DrawState::default();
+    let surface = render(&config, Some(&signal), Some(20.0), 0);
+    let (w, h) = (surface.width(), surface.height());
+
+    // 8px of shadow on the left, 16px on the right and below, 20px margin above
+    let (x, y, bw, bh) = draw::background_rect(&config, Some(&signal), &state, w, h);
+    assert_eq!((x, y), (8, 20));
+    assert_eq!((bw, bh), (w - 24, h - 40));
+
+    // The rectangle follows animation offsets
+    let moved = DrawState { offset_x: 5.0, offset_y: -3.0, ..DrawState::default() };
+    assert_eq!(draw::background_rect(&config, Some(&signal), &moved, w, h).0, 13);
+    assert_eq!(draw::background_rect(&config, Some(&signal), &moved, w, h).1, 17);
+}